@@ -0,0 +1,193 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nalgebra_glm::Vec2;
+
+use raycaster::cast_ray::cast_ray;
+use raycaster::door::{find_doors, Door};
+use raycaster::framebuffer::Framebuffer;
+use raycaster::maze::{find_cell, load_maze};
+use raycaster::player::Player;
+use raycaster::minimap::{render_minimap, MinimapMode};
+use raycaster::render::{render2d, render3d, render_cat_sprite, set_render_threads, Camera2d, VIEW2D_BLOCKS};
+use raycaster::world::{Level, World};
+
+const MAZES: [&str; 2] = ["maze.txt", "maze2.txt"];
+const RESOLUTIONS: [(usize, usize); 3] = [(320, 200), (640, 480), (1300, 900)];
+
+// Escena de prueba: laberinto cargado, tamaño de bloque como en `main` y
+// jugador en la primera celda libre mirando hacia el este
+struct Scene {
+    maze: Vec<Vec<char>>,
+    doors: Vec<Door>,
+    block_size: usize,
+    player: Player,
+}
+
+impl Scene {
+    fn new(maze_file: &str, width: usize, height: usize) -> Self {
+        let maze = load_maze(maze_file);
+        let block_size = (width / maze[0].len()).min(height / maze.len());
+        let (col, row) = find_cell(&maze, ' ').expect("El laberinto no tiene celdas libres");
+        let pos = Vec2::new(
+            (col as f32 + 0.5) * block_size as f32,
+            (row as f32 + 0.5) * block_size as f32,
+        );
+        let player = Player::new(pos, 0.0, std::f32::consts::PI / 3.0);
+        let doors = find_doors(&maze);
+        Self { maze, doors, block_size, player }
+    }
+
+    // Posición del gato dos celdas por delante del jugador
+    fn cat_pos(&self) -> Vec2 {
+        self.player.pos + Vec2::new(2.0 * self.block_size as f32, 0.0)
+    }
+}
+
+fn bench_cast_ray(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cast_ray");
+    let (width, height) = (1300, 900);
+    let mut buffer = vec![0u8; width * height * 4];
+
+    for maze_file in MAZES {
+        let scene = Scene::new(maze_file, width, height);
+        group.throughput(Throughput::Elements(width as u64));
+        group.bench_function(BenchmarkId::from_parameter(maze_file), |b| {
+            let mut framebuffer = Framebuffer::new(width, height, &mut buffer);
+            b.iter(|| {
+                for i in 0..width {
+                    let current_ray = i as f32 / width as f32;
+                    let a = scene.player.a - (scene.player.fov / 2.0) + (scene.player.fov * current_ray);
+                    black_box(cast_ray(&mut framebuffer, &scene.maze, &scene.doors, &scene.player, a, scene.block_size, false));
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_render3d(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render3d");
+
+    for maze_file in MAZES {
+        for (width, height) in RESOLUTIONS {
+            let scene = Scene::new(maze_file, width, height);
+            let mut buffer = vec![0u8; width * height * 4];
+            let id = BenchmarkId::new(maze_file, format!("{}x{}", width, height));
+            group.throughput(Throughput::Elements(width as u64));
+            group.bench_function(id, |b| {
+                let mut framebuffer = Framebuffer::new(width, height, &mut buffer);
+                b.iter(|| render3d(&mut framebuffer, &scene.player, scene.block_size, &scene.maze, &scene.doors, false));
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_render3d_threads(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render3d_threads");
+    let (width, height) = (1300, 900);
+    let scene = Scene::new(MAZES[0], width, height);
+    let mut buffer = vec![0u8; width * height * 4];
+
+    for threads in [1, 2, 4, 8] {
+        set_render_threads(threads).unwrap();
+        group.bench_function(BenchmarkId::from_parameter(threads), |b| {
+            let mut framebuffer = Framebuffer::new(width, height, &mut buffer);
+            b.iter(|| render3d(&mut framebuffer, &scene.player, scene.block_size, &scene.maze, &scene.doors, false));
+        });
+    }
+    set_render_threads(0).unwrap();
+    group.finish();
+}
+
+fn bench_render2d(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render2d");
+    let (width, height) = (1300, 900);
+    let mut buffer = vec![0u8; width * height * 4];
+
+    for maze_file in MAZES {
+        let scene = Scene::new(maze_file, width, height);
+        let camera = Camera2d::follow(scene.player.pos, &scene.maze, scene.block_size, VIEW2D_BLOCKS, width, height);
+        group.bench_function(BenchmarkId::from_parameter(maze_file), |b| {
+            let mut framebuffer = Framebuffer::new(width, height, &mut buffer);
+            b.iter(|| render2d(&mut framebuffer, &scene.player, &scene.maze, &scene.doors, &camera, scene.block_size));
+        });
+    }
+    group.finish();
+}
+
+fn bench_minimap(c: &mut Criterion) {
+    let mut group = c.benchmark_group("minimap");
+    let (width, height) = (1300, 900);
+    let mut buffer = vec![0u8; width * height * 4];
+
+    for maze_file in MAZES {
+        // Con todo el laberinto descubierto, que es lo más caro de dibujar
+        let mut world = World::new(&Level::load(maze_file, 0));
+        world.explored.iter_mut().for_each(|row| row.fill(true));
+        group.bench_function(BenchmarkId::from_parameter(maze_file), |b| {
+            let mut framebuffer = Framebuffer::new(width, height, &mut buffer);
+            b.iter(|| render_minimap(&mut framebuffer, &world, MinimapMode::Rotating, 1, 200));
+        });
+    }
+    group.finish();
+}
+
+fn bench_sprite(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cat_sprite");
+
+    for (width, height) in RESOLUTIONS {
+        let scene = Scene::new(MAZES[0], width, height);
+        let cat_pos = scene.cat_pos();
+        let distance_to_projection_plane = width as f32 / 2.0 / (scene.player.fov / 2.0).tan();
+        let mut buffer = vec![0u8; width * height * 4];
+        group.bench_function(BenchmarkId::from_parameter(format!("{}x{}", width, height)), |b| {
+            let mut framebuffer = Framebuffer::new(width, height, &mut buffer);
+            b.iter(|| {
+                render_cat_sprite(
+                    &mut framebuffer,
+                    &scene.player,
+                    cat_pos,
+                    distance_to_projection_plane,
+                    scene.block_size,
+                    &scene.maze,
+                    300.0,
+                )
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_framebuffer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("framebuffer");
+
+    for (width, height) in RESOLUTIONS {
+        let mut buffer = vec![0u8; width * height * 4];
+        group.throughput(Throughput::Elements((width * height) as u64));
+        group.bench_function(BenchmarkId::new("clear", format!("{}x{}", width, height)), |b| {
+            let mut framebuffer = Framebuffer::new(width, height, &mut buffer);
+            b.iter(|| framebuffer.clear(black_box([0, 0, 0, 0xFF])));
+        });
+    }
+
+    let (width, height) = (1300, 900);
+    let mut buffer = vec![0u8; width * height * 4];
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("draw_text", |b| {
+        let mut framebuffer = Framebuffer::new(width, height, &mut buffer);
+        b.iter(|| framebuffer.draw_text(black_box("FPS: 60"), 10, 10, 40.0));
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_cast_ray,
+    bench_render3d,
+    bench_render3d_threads,
+    bench_render2d,
+    bench_minimap,
+    bench_sprite,
+    bench_framebuffer
+);
+criterion_main!(benches);
@@ -0,0 +1,459 @@
+use nalgebra_glm::Vec2;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[cfg(feature = "audio")]
+use crate::audio_rodio::RodioBackend;
+
+use crate::game::{Game, GameEvent, GameState};
+use crate::maze::Floor;
+use crate::player::Player;
+use crate::render::has_line_of_sight;
+use crate::settings::Settings;
+use crate::world::World;
+
+// Sonidos del juego. Los que no estén en `assets/sounds` se saltan
+pub const MENU_MUSIC: &str = "assets/sounds/Walking_Forest.mp3";
+// Música de cada nivel; si hay más niveles que pistas se vuelve a empezar
+pub const LEVEL_MUSIC: [&str; 2] = ["assets/sounds/musica_nivel1.wav", "assets/sounds/musica_nivel2.wav"];
+// Música de las pantallas finales, debajo del remate
+pub const WIN_MUSIC: &str = "assets/sounds/musica_victoria.wav";
+pub const LOSE_MUSIC: &str = "assets/sounds/musica_derrota.wav";
+// Capa que sube de volumen cuando el gato se acerca
+pub const TENSION_LAYER: &str = "assets/sounds/tension.wav";
+pub const STINGER_WIN: &str = "assets/sounds/victoria.wav";
+pub const STINGER_LOSE: &str = "assets/sounds/derrota.wav";
+pub const STEP_DIRT: &str = "assets/sounds/paso_tierra.wav";
+pub const STEP_STONE: &str = "assets/sounds/paso_piedra.wav";
+pub const STEP_GRASS: &str = "assets/sounds/paso_hierba.wav";
+pub const SFX_CARROT: &str = "assets/sounds/zanahoria.wav";
+pub const SFX_HURT: &str = "assets/sounds/golpe.wav";
+pub const SFX_LIFE_LOST: &str = "assets/sounds/vida_perdida.wav";
+pub const SFX_HEAL: &str = "assets/sounds/corazon.wav";
+pub const SFX_KEY: &str = "assets/sounds/llave.wav";
+pub const SFX_DOOR_OPEN: &str = "assets/sounds/puerta.wav";
+pub const SFX_DOOR_LOCKED: &str = "assets/sounds/puerta_cerrada.wav";
+pub const SFX_LEVEL_COMPLETE: &str = "assets/sounds/nivel.wav";
+pub const SFX_MEOW: &str = "assets/sounds/maullido.wav";
+pub const CARROT_HUM_LOOP: &str = "assets/sounds/zumbido.wav";
+
+// Distancia, en bloques, a partir de la cual un sonido ya no se oye
+pub const HEARING_BLOCKS: f32 = 12.0;
+// Volumen que queda cuando hay una pared en medio
+const MUFFLED_GAIN: f32 = 0.4;
+
+// Segundos de los fundidos entre pistas
+const MUSIC_FADE_TIME: f32 = 1.5;
+// Volumen de la música con el juego en pausa y mientras el gato persigue
+const PAUSED_MUSIC_GAIN: f32 = 0.5;
+const CHASE_MUSIC_GAIN: f32 = 0.35;
+// El gato persigue si se le ve a menos de estos bloques
+const CHASE_BLOCKS: f32 = 4.0;
+// La capa de tensión empieza a oírse con el gato a esta distancia, en bloques
+const TENSION_BLOCKS: f32 = 8.0;
+// Lo que cambian por segundo, como mucho, la atenuación y la tensión
+const MUSIC_GAIN_SPEED: f32 = 1.5;
+
+// Canales de audio con volumen propio
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Bus {
+    Music,
+    Sfx,
+    Footsteps,
+    Ambient, // Bucles que salen de un punto del laberinto
+    Tension, // Capa de la música que depende del gato
+}
+
+pub const BUSES: [Bus; 5] = [Bus::Music, Bus::Sfx, Bus::Footsteps, Bus::Ambient, Bus::Tension];
+
+impl Bus {
+    pub fn name(self) -> &'static str {
+        match self {
+            Bus::Music => "music",
+            Bus::Sfx => "sfx",
+            Bus::Footsteps => "footsteps",
+            Bus::Ambient => "ambient",
+            Bus::Tension => "tension",
+        }
+    }
+}
+
+// Cómo se oye un sonido desde la posición del jugador
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spatial {
+    pub gain: f32,
+    pub pan: f32, // -1 = todo a la izquierda, 1 = todo a la derecha
+    pub muffled: bool,
+}
+
+impl Spatial {
+    pub const CENTER: Spatial = Spatial { gain: 1.0, pan: 0.0, muffled: false };
+    pub const SILENT: Spatial = Spatial { gain: 0.0, pan: 0.0, muffled: false };
+
+    // Volumen de los canales izquierdo y derecho, con reparto de igual potencia
+    pub fn channel_volumes(self) -> [f32; 2] {
+        let angle = (self.pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+        [self.gain * angle.cos(), self.gain * angle.sin()]
+    }
+}
+
+// Sonido que sale de `pos`: se atenúa con la distancia, se oye por el lado en
+// que queda respecto a donde mira el jugador y se apaga si hay paredes en medio
+pub fn spatialize(listener: &Player, pos: Vec2, maze: &[Vec<char>], block_size: usize) -> Spatial {
+    let to_source = pos - listener.pos;
+    let distance = to_source.magnitude() / block_size as f32;
+    let mut gain = (1.0 - distance / HEARING_BLOCKS).max(0.0).powi(2);
+    if gain <= 0.0 {
+        return Spatial::SILENT;
+    }
+    // Los ángulos crecen hacia la derecha de la pantalla
+    let pan = if distance > 0.1 { (to_source.y.atan2(to_source.x) - listener.a).sin() } else { 0.0 };
+    let muffled = !has_line_of_sight(&listener.pos, &pos, maze, block_size);
+    if muffled {
+        gain *= MUFFLED_GAIN;
+    }
+    Spatial { gain, pan, muffled }
+}
+
+// Lo que hace el sonido de verdad. Cada bus tiene como mucho un sonido en bucle;
+// los efectos sueltos se mezclan encima
+pub trait AudioBackend {
+    fn name(&self) -> &'static str;
+    // Sustituye el bucle del bus; empieza en pausa si `paused`
+    fn play_loop(&mut self, bus: Bus, sound: &str, paused: bool);
+    // Sustituye el bucle del bus fundiendo el anterior con el nuevo (o con
+    // silencio si `sound` es `None`) durante `seconds`
+    fn crossfade(&mut self, bus: Bus, sound: Option<&str>, seconds: f32);
+    fn play_once(&mut self, bus: Bus, sound: &str, volume: f32);
+    // Como `play_once` pero situado en el espacio
+    fn play_at(&mut self, bus: Bus, sound: &str, volume: f32, spatial: Spatial);
+    // Bucle situado en el espacio; se mueve con `set_spatial`
+    fn play_loop_at(&mut self, bus: Bus, sound: &str, spatial: Spatial);
+    fn set_spatial(&mut self, bus: Bus, spatial: Spatial);
+    fn set_paused(&mut self, bus: Bus, paused: bool);
+    fn set_volume(&mut self, bus: Bus, volume: f32);
+    // Multiplica el volumen del bus sin tocar el de las opciones
+    fn set_gain(&mut self, bus: Bus, gain: f32);
+    // Avanza los fundidos
+    fn update(&mut self, dt: f32);
+}
+
+// Sin dispositivo de audio: no hace nada
+pub struct NullBackend;
+
+impl AudioBackend for NullBackend {
+    fn name(&self) -> &'static str {
+        "null"
+    }
+    fn play_loop(&mut self, _bus: Bus, _sound: &str, _paused: bool) {}
+    fn crossfade(&mut self, _bus: Bus, _sound: Option<&str>, _seconds: f32) {}
+    fn play_once(&mut self, _bus: Bus, _sound: &str, _volume: f32) {}
+    fn play_at(&mut self, _bus: Bus, _sound: &str, _volume: f32, _spatial: Spatial) {}
+    fn play_loop_at(&mut self, _bus: Bus, _sound: &str, _spatial: Spatial) {}
+    fn set_spatial(&mut self, _bus: Bus, _spatial: Spatial) {}
+    fn set_paused(&mut self, _bus: Bus, _paused: bool) {}
+    fn set_volume(&mut self, _bus: Bus, _volume: f32) {}
+    fn set_gain(&mut self, _bus: Bus, _gain: f32) {}
+    fn update(&mut self, _dt: f32) {}
+}
+
+// Orden que recibió el backend, tal como la guarda `RecordingBackend`
+#[derive(Clone, Debug, PartialEq)]
+pub enum AudioCommand {
+    PlayLoop { bus: Bus, sound: String, paused: bool },
+    Crossfade { bus: Bus, sound: Option<String>, seconds: f32 },
+    PlayOnce { bus: Bus, sound: String, volume: f32 },
+    PlayAt { bus: Bus, sound: String, volume: f32, spatial: Spatial },
+    PlayLoopAt { bus: Bus, sound: String, spatial: Spatial },
+    SetSpatial { bus: Bus, spatial: Spatial },
+    SetPaused { bus: Bus, paused: bool },
+    SetVolume { bus: Bus, volume: f32 },
+    SetGain { bus: Bus, gain: f32 },
+}
+
+// Apunta todo lo que se le pide, para comprobar qué sonidos se dispararon sin
+// oír nada. `log` sigue siendo válido después de pasarle el backend al gestor
+#[derive(Default)]
+pub struct RecordingBackend {
+    pub log: Rc<RefCell<Vec<AudioCommand>>>,
+}
+
+impl RecordingBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AudioBackend for RecordingBackend {
+    fn name(&self) -> &'static str {
+        "recording"
+    }
+    fn play_loop(&mut self, bus: Bus, sound: &str, paused: bool) {
+        self.log.borrow_mut().push(AudioCommand::PlayLoop { bus, sound: sound.to_string(), paused });
+    }
+    fn crossfade(&mut self, bus: Bus, sound: Option<&str>, seconds: f32) {
+        self.log.borrow_mut().push(AudioCommand::Crossfade { bus, sound: sound.map(str::to_string), seconds });
+    }
+    fn play_once(&mut self, bus: Bus, sound: &str, volume: f32) {
+        self.log.borrow_mut().push(AudioCommand::PlayOnce { bus, sound: sound.to_string(), volume });
+    }
+    fn play_at(&mut self, bus: Bus, sound: &str, volume: f32, spatial: Spatial) {
+        self.log.borrow_mut().push(AudioCommand::PlayAt { bus, sound: sound.to_string(), volume, spatial });
+    }
+    fn play_loop_at(&mut self, bus: Bus, sound: &str, spatial: Spatial) {
+        self.log.borrow_mut().push(AudioCommand::PlayLoopAt { bus, sound: sound.to_string(), spatial });
+    }
+    fn set_spatial(&mut self, bus: Bus, spatial: Spatial) {
+        self.log.borrow_mut().push(AudioCommand::SetSpatial { bus, spatial });
+    }
+    fn set_paused(&mut self, bus: Bus, paused: bool) {
+        self.log.borrow_mut().push(AudioCommand::SetPaused { bus, paused });
+    }
+    fn set_volume(&mut self, bus: Bus, volume: f32) {
+        self.log.borrow_mut().push(AudioCommand::SetVolume { bus, volume });
+    }
+    fn set_gain(&mut self, bus: Bus, gain: f32) {
+        self.log.borrow_mut().push(AudioCommand::SetGain { bus, gain });
+    }
+    fn update(&mut self, _dt: f32) {}
+}
+
+// Punto único por el que pasa todo el audio del juego
+pub struct AudioManager {
+    backend: Box<dyn AudioBackend>,
+    volumes: HashMap<Bus, f32>,
+    carrot_hum: Spatial,         // Última posición enviada del zumbido
+    music: Option<&'static str>, // Pista que está sonando (o entrando)
+    music_gain: f32,             // Atenuación de la música: en pausa, con el gato cerca
+    tension: f32,                // Volumen de la capa de tensión
+}
+
+impl AudioManager {
+    pub fn new(backend: Box<dyn AudioBackend>) -> Self {
+        Self {
+            backend,
+            volumes: BUSES.iter().map(|&bus| (bus, 1.0)).collect(),
+            carrot_hum: Spatial::SILENT,
+            music: None,
+            music_gain: 1.0,
+            tension: 0.0,
+        }
+    }
+
+    // Rodio si hay dispositivo de salida; si no, el juego sigue sin sonido
+    #[cfg(feature = "audio")]
+    pub fn open_default() -> Self {
+        match RodioBackend::open() {
+            Ok(backend) => Self::new(Box::new(backend)),
+            Err(err) => {
+                eprintln!("Sin audio ({}); se sigue sin sonido", err);
+                Self::null()
+            }
+        }
+    }
+
+    pub fn null() -> Self {
+        Self::new(Box::new(NullBackend))
+    }
+
+    // Gestor con un `RecordingBackend` y el registro de lo que se le pide
+    pub fn recording() -> (Self, Rc<RefCell<Vec<AudioCommand>>>) {
+        let backend = RecordingBackend::new();
+        let log = Rc::clone(&backend.log);
+        (Self::new(Box::new(backend)), log)
+    }
+
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    pub fn volume(&self, bus: Bus) -> f32 {
+        self.volumes[&bus]
+    }
+
+    pub fn set_volume(&mut self, bus: Bus, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        self.volumes.insert(bus, volume);
+        self.backend.set_volume(bus, volume);
+    }
+
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.set_volume(Bus::Music, settings.music_volume);
+        self.set_volume(Bus::Sfx, settings.sfx_volume);
+        self.set_volume(Bus::Footsteps, settings.footsteps_volume);
+        self.set_volume(Bus::Ambient, settings.sfx_volume);
+        self.set_volume(Bus::Tension, settings.music_volume);
+    }
+
+    // Capa de tensión y zumbido de las zanahorias, callados hasta que haga
+    // falta. La música entra con el primer `update`
+    pub fn start(&mut self) {
+        self.tension = 0.0;
+        self.backend.set_gain(Bus::Tension, 0.0);
+        self.backend.play_loop(Bus::Tension, TENSION_LAYER, false);
+        self.backend.play_loop_at(Bus::Ambient, CARROT_HUM_LOOP, Spatial::SILENT);
+        self.carrot_hum = Spatial::SILENT;
+    }
+
+    pub fn play_sfx(&mut self, sound: &str) {
+        self.backend.play_once(Bus::Sfx, sound, 1.0);
+    }
+
+    // Efecto que sale de un punto del laberinto
+    pub fn play_sfx_at(&mut self, sound: &str, pos: Vec2, world: &World) {
+        let spatial = spatialize(&world.player, pos, &world.maze, world.block_size);
+        if spatial.gain > 0.0 {
+            self.backend.play_at(Bus::Sfx, sound, 1.0, spatial);
+        }
+    }
+
+    // Una vez por fotograma, con los segundos desde el anterior: cambia de
+    // pista si hace falta, ajusta la música al gato y mueve los bucles situados
+    pub fn update(&mut self, game: &Game, dt: f32) {
+        let track = music_for(game);
+        if track != self.music {
+            self.music = track;
+            self.backend.crossfade(Bus::Music, track, MUSIC_FADE_TIME);
+        }
+
+        let (music_gain, tension) = match game.state {
+            GameState::Playing { .. } => match cat_threat(&game.world) {
+                Some((distance, visible)) => {
+                    let chasing = visible && distance < CHASE_BLOCKS;
+                    let closeness = (1.0 - distance / TENSION_BLOCKS).max(0.0);
+                    (if chasing { CHASE_MUSIC_GAIN } else { 1.0 }, closeness * closeness)
+                }
+                None => (1.0, 0.0),
+            },
+            GameState::Paused { .. } | GameState::Options { .. } | GameState::SaveSlots { view: Some(_), .. } => {
+                (PAUSED_MUSIC_GAIN, 0.0)
+            }
+            _ => (1.0, 0.0),
+        };
+        let step = MUSIC_GAIN_SPEED * dt;
+        let music_gain = approach(self.music_gain, music_gain, step);
+        if music_gain != self.music_gain {
+            self.music_gain = music_gain;
+            self.backend.set_gain(Bus::Music, music_gain);
+        }
+        let tension = approach(self.tension, tension, step);
+        if tension != self.tension {
+            self.tension = tension;
+            self.backend.set_gain(Bus::Tension, tension);
+        }
+
+        let hum = match game.state {
+            GameState::Playing { .. } => nearest_carrot(&game.world)
+                .map(|pos| spatialize(&game.world.player, pos, &game.world.maze, game.world.block_size))
+                .unwrap_or(Spatial::SILENT),
+            _ => Spatial::SILENT,
+        };
+        if hum != self.carrot_hum {
+            self.carrot_hum = hum;
+            self.backend.set_spatial(Bus::Ambient, hum);
+        }
+        self.backend.update(dt);
+    }
+
+    // Remate musical a todo volumen, aunque la música estuviera atenuada
+    fn play_stinger(&mut self, sound: &str) {
+        self.music_gain = 1.0;
+        self.backend.set_gain(Bus::Music, 1.0);
+        self.backend.play_once(Bus::Music, sound, 1.0);
+    }
+
+    // Sonido de cada evento del juego
+    pub fn handle_event(&mut self, event: &GameEvent, world: &World) {
+        let sfx = match event {
+            GameEvent::DoorOpened { pos } => {
+                self.play_sfx_at(SFX_DOOR_OPEN, *pos, world);
+                return;
+            }
+            GameEvent::CatMeow { pos } => {
+                self.play_sfx_at(SFX_MEOW, *pos, world);
+                return;
+            }
+            GameEvent::Footstep { floor } => {
+                self.backend.play_once(Bus::Footsteps, step_sound(*floor), 1.0);
+                return;
+            }
+            GameEvent::SettingsChanged => return,
+            GameEvent::CarrotCollected { .. } => SFX_CARROT,
+            GameEvent::Hurt { .. } => SFX_HURT,
+            GameEvent::LifeLost { .. } => SFX_LIFE_LOST,
+            GameEvent::Healed { .. } => SFX_HEAL,
+            GameEvent::KeyCollected(_) => SFX_KEY,
+            GameEvent::DoorLocked(_) => SFX_DOOR_LOCKED,
+            GameEvent::LevelCompleted { .. } => SFX_LEVEL_COMPLETE,
+            GameEvent::Won => {
+                self.play_stinger(STINGER_WIN);
+                return;
+            }
+            GameEvent::Lost => {
+                self.play_stinger(STINGER_LOSE);
+                return;
+            }
+            _ => return,
+        };
+        self.play_sfx(sfx);
+    }
+}
+
+// Pista de cada estado: la del menú, la del nivel en juego (también en pausa)
+// o la de la pantalla final
+fn music_for(game: &Game) -> Option<&'static str> {
+    match game.state {
+        GameState::Intro | GameState::Menu { .. } | GameState::SaveSlots { view: None, .. } => Some(MENU_MUSIC),
+        GameState::Won => Some(WIN_MUSIC),
+        GameState::Lost => Some(LOSE_MUSIC),
+        _ => Some(LEVEL_MUSIC[game.level % LEVEL_MUSIC.len()]),
+    }
+}
+
+// Distancia en bloques al gato más cercano y si se le ve
+fn cat_threat(world: &World) -> Option<(f32, bool)> {
+    let cat = world
+        .cats
+        .iter()
+        .map(|cat| cat.pos)
+        .min_by(|a, b| (a - world.player.pos).magnitude().total_cmp(&(b - world.player.pos).magnitude()))?;
+    let distance = (cat - world.player.pos).magnitude() / world.block_size as f32;
+    Some((distance, has_line_of_sight(&world.player.pos, &cat, &world.maze, world.block_size)))
+}
+
+// `current` hacia `target` sin pasar de `step`
+fn approach(current: f32, target: f32, step: f32) -> f32 {
+    if current < target {
+        (current + step).min(target)
+    } else {
+        (current - step).max(target)
+    }
+}
+
+fn step_sound(floor: Floor) -> &'static str {
+    match floor {
+        Floor::Dirt => STEP_DIRT,
+        Floor::Stone => STEP_STONE,
+        Floor::Grass => STEP_GRASS,
+    }
+}
+
+// Centro de la zanahoria más cercana al jugador
+fn nearest_carrot(world: &World) -> Option<Vec2> {
+    let bs = world.block_size as f32;
+    world
+        .maze
+        .iter()
+        .enumerate()
+        .flat_map(|(row, line)| {
+            line.iter().enumerate().filter(|(_, &tile)| tile == 'z').map(move |(col, _)| {
+                Vec2::new((col as f32 + 0.5) * bs, (row as f32 + 0.5) * bs)
+            })
+        })
+        .min_by(|a, b| {
+            let da = (a - world.player.pos).magnitude();
+            let db = (b - world.player.pos).magnitude();
+            da.total_cmp(&db)
+        })
+}
@@ -0,0 +1,263 @@
+// Salida de sonido de verdad, con rodio. Solo con la característica `audio`,
+// que está activa por defecto; `simulate` y `replay` no la necesitan
+use rodio::source::{BltFilter, ChannelVolume, SamplesConverter};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sample, Sink, Source};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::audio::{AudioBackend, Bus, Spatial};
+
+// Frecuencia de corte del filtro cuando hay una pared en medio
+const MUFFLED_CUTOFF: u32 = 700;
+
+// Sonido pasado a mono, filtrado y repartido entre los dos canales
+type Positioned<S> = ChannelVolume<BltFilter<ChannelVolume<SamplesConverter<S, f32>>>>;
+
+fn positioned<S>(source: S, spatial: Spatial) -> Positioned<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    let channels = source.channels().max(1);
+    let mono = ChannelVolume::new(source.convert_samples(), vec![1.0 / channels as f32]);
+    let cutoff = cutoff(spatial, mono.sample_rate());
+    ChannelVolume::new(mono.low_pass(cutoff), spatial.channel_volumes().to_vec())
+}
+
+fn update_positioned<S>(source: &mut Positioned<S>, spatial: Spatial)
+where
+    S: Source,
+    S::Item: Sample,
+{
+    let [left, right] = spatial.channel_volumes();
+    source.set_volume(0, left);
+    source.set_volume(1, right);
+    let cutoff = cutoff(spatial, source.inner().inner().sample_rate());
+    source.inner_mut().to_low_pass(cutoff);
+}
+
+// Sin paredes en medio el filtro deja pasar casi todo
+fn cutoff(spatial: Spatial, sample_rate: u32) -> u32 {
+    if spatial.muffled {
+        MUFFLED_CUTOFF
+    } else {
+        sample_rate * 9 / 20
+    }
+}
+
+// Bucle que suena en un bus, con su fundido de entrada o de salida
+struct Loop {
+    sink: Sink,
+    fade: f32,       // 0 = en silencio, 1 = a todo su volumen
+    fade_speed: f32, // Cuánto cambia `fade` por segundo; negativo al apagarse
+}
+
+impl Loop {
+    fn new(sink: Sink, fade_seconds: f32) -> Self {
+        if fade_seconds > 0.0 {
+            Self { sink, fade: 0.0, fade_speed: 1.0 / fade_seconds }
+        } else {
+            Self { sink, fade: 1.0, fade_speed: 0.0 }
+        }
+    }
+}
+
+// Salida real con rodio: un `Sink` por bus para los bucles y uno suelto por
+// cada efecto, para que se puedan solapar
+pub struct RodioBackend {
+    _stream: OutputStream, // Si se suelta se corta el sonido
+    handle: OutputStreamHandle,
+    loops: HashMap<Bus, Loop>,
+    fading_out: Vec<(Bus, Loop)>, // Bucles sustituidos que aún se están apagando
+    positions: HashMap<Bus, Arc<Mutex<Spatial>>>, // Posición de los bucles situados
+    volumes: HashMap<Bus, f32>,
+    gains: HashMap<Bus, f32>,
+    cache: HashMap<String, Arc<[u8]>>,
+    missing: HashSet<String>, // Archivos que ya se avisó que no están
+}
+
+impl RodioBackend {
+    pub fn open() -> Result<Self, String> {
+        let (stream, handle) = OutputStream::try_default().map_err(|err| err.to_string())?;
+        Ok(Self {
+            _stream: stream,
+            handle,
+            loops: HashMap::new(),
+            fading_out: Vec::new(),
+            positions: HashMap::new(),
+            volumes: HashMap::new(),
+            gains: HashMap::new(),
+            cache: HashMap::new(),
+            missing: HashSet::new(),
+        })
+    }
+
+    fn decode(&mut self, sound: &str) -> Option<Decoder<Cursor<Arc<[u8]>>>> {
+        if self.missing.contains(sound) {
+            return None;
+        }
+        let bytes = match self.cache.get(sound) {
+            Some(bytes) => Arc::clone(bytes),
+            None => match fs::read(sound) {
+                Ok(bytes) => {
+                    let bytes: Arc<[u8]> = bytes.into();
+                    self.cache.insert(sound.to_string(), Arc::clone(&bytes));
+                    bytes
+                }
+                Err(err) => {
+                    eprintln!("Sonido no disponible {}: {}", sound, err);
+                    self.missing.insert(sound.to_string());
+                    return None;
+                }
+            },
+        };
+        match Decoder::new(Cursor::new(bytes)) {
+            Ok(decoder) => Some(decoder),
+            Err(err) => {
+                eprintln!("No se pudo decodificar {}: {}", sound, err);
+                self.missing.insert(sound.to_string());
+                None
+            }
+        }
+    }
+
+    // Volumen de las opciones por la atenuación del bus
+    fn level(&self, bus: Bus) -> f32 {
+        self.volumes.get(&bus).copied().unwrap_or(1.0) * self.gains.get(&bus).copied().unwrap_or(1.0)
+    }
+
+    fn refresh(&self, bus: Bus) {
+        let level = self.level(bus);
+        if let Some(current) = self.loops.get(&bus) {
+            current.sink.set_volume(level * current.fade);
+        }
+        for (_, old) in self.fading_out.iter().filter(|(b, _)| *b == bus) {
+            old.sink.set_volume(level * old.fade);
+        }
+    }
+
+    // Quita el bucle del bus; con `fade_seconds` se sigue oyendo mientras se apaga
+    fn stop_loop(&mut self, bus: Bus, fade_seconds: f32) {
+        self.positions.remove(&bus);
+        if let Some(mut old) = self.loops.remove(&bus) {
+            if fade_seconds > 0.0 && old.fade > 0.0 {
+                old.fade_speed = -1.0 / fade_seconds;
+                self.fading_out.push((bus, old));
+            }
+        }
+    }
+
+    fn start_loop(&mut self, bus: Bus, sound: &str, fade_seconds: f32) -> Option<&Sink> {
+        let decoder = self.decode(sound)?;
+        let sink = Sink::try_new(&self.handle).ok()?;
+        sink.append(decoder.repeat_infinite());
+        self.loops.insert(bus, Loop::new(sink, fade_seconds));
+        self.refresh(bus);
+        self.loops.get(&bus).map(|l| &l.sink)
+    }
+}
+
+impl AudioBackend for RodioBackend {
+    fn name(&self) -> &'static str {
+        "rodio"
+    }
+
+    fn play_loop(&mut self, bus: Bus, sound: &str, paused: bool) {
+        self.stop_loop(bus, 0.0);
+        if let Some(sink) = self.start_loop(bus, sound, 0.0) {
+            if paused {
+                sink.pause();
+            }
+        }
+    }
+
+    fn crossfade(&mut self, bus: Bus, sound: Option<&str>, seconds: f32) {
+        self.stop_loop(bus, seconds);
+        if let Some(sound) = sound {
+            self.start_loop(bus, sound, seconds);
+        }
+    }
+
+    fn play_once(&mut self, bus: Bus, sound: &str, volume: f32) {
+        let Some(decoder) = self.decode(sound) else { return };
+        let Ok(sink) = Sink::try_new(&self.handle) else { return };
+        sink.set_volume(self.level(bus) * volume);
+        sink.append(decoder);
+        sink.detach();
+    }
+
+    fn play_at(&mut self, bus: Bus, sound: &str, volume: f32, spatial: Spatial) {
+        if spatial.gain <= 0.0 {
+            return;
+        }
+        let Some(decoder) = self.decode(sound) else { return };
+        let Ok(sink) = Sink::try_new(&self.handle) else { return };
+        sink.set_volume(self.level(bus) * volume);
+        sink.append(positioned(decoder, spatial));
+        sink.detach();
+    }
+
+    fn play_loop_at(&mut self, bus: Bus, sound: &str, spatial: Spatial) {
+        self.stop_loop(bus, 0.0);
+        let Some(decoder) = self.decode(sound) else { return };
+        let Ok(sink) = Sink::try_new(&self.handle) else { return };
+        // El hilo de audio lee la posición compartida cada pocos milisegundos
+        let position = Arc::new(Mutex::new(spatial));
+        let shared = Arc::clone(&position);
+        let source = positioned(decoder.repeat_infinite(), spatial).periodic_access(Duration::from_millis(20), move |source| {
+            if let Ok(spatial) = shared.lock() {
+                update_positioned(source, *spatial);
+            }
+        });
+        sink.append(source);
+        self.loops.insert(bus, Loop::new(sink, 0.0));
+        self.positions.insert(bus, position);
+        self.refresh(bus);
+    }
+
+    fn set_spatial(&mut self, bus: Bus, spatial: Spatial) {
+        if let Some(mut position) = self.positions.get(&bus).and_then(|p| p.lock().ok()) {
+            *position = spatial;
+        }
+    }
+
+    fn set_paused(&mut self, bus: Bus, paused: bool) {
+        if let Some(current) = self.loops.get(&bus) {
+            if paused {
+                current.sink.pause();
+            } else {
+                current.sink.play();
+            }
+        }
+    }
+
+    fn set_volume(&mut self, bus: Bus, volume: f32) {
+        self.volumes.insert(bus, volume);
+        self.refresh(bus);
+    }
+
+    fn set_gain(&mut self, bus: Bus, gain: f32) {
+        self.gains.insert(bus, gain);
+        self.refresh(bus);
+    }
+
+    fn update(&mut self, dt: f32) {
+        let mut changed = HashSet::new();
+        for (bus, track) in self.loops.iter_mut().chain(self.fading_out.iter_mut().map(|(b, l)| (&*b, l))) {
+            if track.fade_speed != 0.0 {
+                track.fade = (track.fade + track.fade_speed * dt).clamp(0.0, 1.0);
+                if track.fade >= 1.0 {
+                    track.fade_speed = 0.0;
+                }
+                changed.insert(*bus);
+            }
+        }
+        self.fading_out.retain(|(_, old)| old.fade > 0.0);
+        for bus in changed {
+            self.refresh(bus);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use nalgebra_glm::Vec2;
+
+use crate::framebuffer::Framebuffer;
+use crate::minimap::{pickup_color, tile_color, CARROT_COLOR, CAT_COLOR, PLAYER_COLOR};
+use crate::render::has_line_of_sight;
+use crate::world::World;
+
+// Zoom: 1 = el laberinto entero cabe en pantalla
+const AUTOMAP_ZOOM: f32 = 1.0;
+const AUTOMAP_ZOOM_MIN: f32 = 0.5;
+const AUTOMAP_ZOOM_MAX: f32 = 8.0;
+const AUTOMAP_ZOOM_STEP: f32 = 1.25;
+// Bloques que se mueve el mapa en cada pulsación
+const AUTOMAP_PAN_BLOCKS: f32 = 2.0;
+// Opacidad del fondo oscuro y de las celdas sobre la escena
+const BACKDROP_ALPHA: f32 = 0.55;
+const CELL_ALPHA: f32 = 0.7;
+const TRAIL_COLOR: [u8; 4] = [0xFF, 0xF0, 0x60, 0xFF];
+
+pub const AUTOMAP_HINT: &str = "I J K L: mover · + / -: zoom · C: centrar · Q: cerrar";
+
+// Mapa a pantalla completa sobre la vista, con el norte arriba
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Automap {
+    pub open: bool,
+    pub zoom: f32,
+    pub pan: Vec2, // Desplazamiento del centro respecto al jugador, en bloques
+}
+
+impl Default for Automap {
+    fn default() -> Self {
+        Self { open: false, zoom: AUTOMAP_ZOOM, pan: Vec2::zeros() }
+    }
+}
+
+impl Automap {
+    // Al abrirse vuelve a seguir al jugador; el zoom se conserva
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.pan = Vec2::zeros();
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.zoom = (self.zoom * AUTOMAP_ZOOM_STEP).min(AUTOMAP_ZOOM_MAX);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.zoom = (self.zoom / AUTOMAP_ZOOM_STEP).max(AUTOMAP_ZOOM_MIN);
+    }
+
+    // `dx`, `dy`: -1, 0 o 1 en la dirección de la pantalla
+    pub fn pan_by(&mut self, dx: f32, dy: f32) {
+        self.pan += Vec2::new(dx, dy) * AUTOMAP_PAN_BLOCKS / self.zoom;
+    }
+
+    pub fn center(&mut self) {
+        self.pan = Vec2::zeros();
+    }
+}
+
+// Celdas exploradas, recorrido, zanahorias recogidas y objetos a la vista,
+// mezclados con lo que ya hay en el framebuffer
+pub fn render_automap(framebuffer: &mut Framebuffer, world: &World, automap: &Automap) {
+    let (w, h) = (framebuffer.get_width() as f32, framebuffer.get_height() as f32);
+    let bs = world.block_size as f32;
+    let (cols, rows) = (world.maze[0].len() as f32, world.maze.len() as f32);
+    let fit = (w * 0.9 / (cols * bs)).min(h * 0.9 / (rows * bs));
+    let scale = fit * automap.zoom;
+    let origin = world.player.pos + automap.pan * bs;
+    let to_screen = |pos: Vec2| (pos - origin) * scale + Vec2::new(w / 2.0, h / 2.0);
+
+    framebuffer.blend_rect(0, 0, w as usize, h as usize, [0x00, 0x00, 0x00, 0xFF], BACKDROP_ALPHA);
+
+    for (row, line) in world.maze.iter().enumerate() {
+        for (col, &tile) in line.iter().enumerate() {
+            if !world.explored[row][col] {
+                continue;
+            }
+            // De esquina a esquina para que las celdas vecinas no se solapen
+            let start = to_screen(Vec2::new(col as f32, row as f32) * bs);
+            let end = to_screen(Vec2::new(col as f32 + 1.0, row as f32 + 1.0) * bs);
+            let (x0, y0, x1, y1) = (start.x.floor(), start.y.floor(), end.x.floor(), end.y.floor());
+            let (width, height) = ((x1 - x0) as usize, (y1 - y0) as usize);
+            framebuffer.blend_rect(x0 as isize, y0 as isize, width, height, tile_color(tile), CELL_ALPHA);
+        }
+    }
+
+    for segment in &world.trail {
+        for pair in segment.windows(2) {
+            let (a, b) = (to_screen(pair[0]), to_screen(pair[1]));
+            framebuffer.draw_line_aa(a.x, a.y, b.x, b.y, TRAIL_COLOR);
+        }
+    }
+
+    let dot = (bs * scale / 6.0).max(2.0) as isize;
+    let cell_center = |col: usize, row: usize| to_screen(Vec2::new(col as f32 + 0.5, row as f32 + 0.5) * bs);
+    // Una cruz donde había cada zanahoria recogida
+    for &(col, row) in &world.collected {
+        let c = cell_center(col, row);
+        let (x, y) = (c.x as isize, c.y as isize);
+        framebuffer.draw_line(x - dot, y - dot, x + dot, y + dot, CARROT_COLOR);
+        framebuffer.draw_line(x - dot, y + dot, x + dot, y - dot, CARROT_COLOR);
+    }
+    for (row, line) in world.maze.iter().enumerate() {
+        for (col, &tile) in line.iter().enumerate() {
+            if let (true, Some(color)) = (world.explored[row][col], pickup_color(tile)) {
+                let c = cell_center(col, row);
+                framebuffer.fill_circle(c.x as isize, c.y as isize, dot, color);
+            }
+        }
+    }
+    for cat in &world.cats {
+        if has_line_of_sight(&world.player.pos, &cat.pos, &world.maze, world.block_size) {
+            let c = to_screen(cat.pos);
+            framebuffer.fill_circle(c.x as isize, c.y as isize, dot + 1, CAT_COLOR);
+        }
+    }
+
+    // Flecha del jugador
+    let a = world.player.a;
+    let length = (bs * scale * 0.5).max(8.0);
+    let p = to_screen(world.player.pos);
+    let point = |angle: f32, distance: f32| p + Vec2::new(angle.cos(), angle.sin()) * distance;
+    let tip = point(a, length);
+    let left = point(a + 2.5, length * 0.7);
+    let right = point(a - 2.5, length * 0.7);
+    for (from, to) in [(tip, left), (tip, right), (left, p), (right, p)] {
+        framebuffer.draw_line_aa(from.x, from.y, to.x, to.y, PLAYER_COLOR);
+    }
+}
@@ -0,0 +1,84 @@
+// Repite una partida grabada con `--record` sin ventana ni audio, muestra el
+// estado final y lo compara con el de la grabación.
+//
+//     cargo run --bin replay -- partida.replay [--frames carpeta|clip.gif] [--every N] [--resolution 640x400]
+use std::process::ExitCode;
+use std::time::Duration;
+
+use raycaster::capture::FrameCapture;
+use raycaster::framebuffer::Framebuffer;
+use raycaster::game::TICK_RATE;
+use raycaster::replay::{Replay, ReplayCheck};
+use raycaster::scaling::RenderResolution;
+use raycaster::world::{WORLD_HEIGHT, WORLD_WIDTH};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(path) = args.get(1).filter(|a| !a.starts_with("--")) else {
+        eprintln!("Uso: replay <archivo> [--frames carpeta|clip.gif] [--every N] [--resolution ANCHOxALTO]");
+        return ExitCode::FAILURE;
+    };
+    let replay = match Replay::load(path) {
+        Ok(replay) => replay,
+        Err(err) => {
+            eprintln!("No se pudo leer {}: {}", path, err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Fotogramas opcionales, en PNG o en un GIF: uno cada `--every` ticks
+    let frames_path = arg_value(&args, "--frames");
+    let every: u64 = arg_value(&args, "--every").map(|n| n.parse().expect("--every espera un número")).unwrap_or(1).max(1);
+    let resolution = arg_value(&args, "--resolution")
+        .map(|r| RenderResolution::parse(r).expect("--resolution espera ANCHOxALTO"))
+        .unwrap_or(RenderResolution::Fixed(WORLD_WIDTH, WORLD_HEIGHT));
+    let mut capture = frames_path.map(|path| {
+        FrameCapture::create(path).unwrap_or_else(|err| panic!("No se pudo grabar en {}: {}", path, err))
+    });
+    let frame_delay = Duration::from_secs_f32(every as f32 / TICK_RATE as f32);
+
+    let mut buffer = Vec::new();
+    let mut frames = 0;
+    let game = replay.play(|game| {
+        let Some(capture) = &mut capture else { return };
+        if game.ticks % every != 0 {
+            return;
+        }
+        let (width, height) = resolution.size(WORLD_WIDTH, WORLD_HEIGHT);
+        buffer.resize(width * height * 4, 0);
+        let mut framebuffer = Framebuffer::new(width, height, &mut buffer);
+        framebuffer.clear([0, 0, 0, 0xFF]);
+        game.render(&mut framebuffer, TICK_RATE);
+
+        capture
+            .add_frame(&buffer, width, height, frame_delay)
+            .unwrap_or_else(|err| panic!("No se pudo guardar el fotograma {}: {}", game.ticks, err));
+        frames += 1;
+    });
+
+    drop(capture); // Cierra el GIF
+    let state = ReplayCheck::of(&game);
+    println!("ticks: {} ({:.2} s)", game.ticks, game.ticks as f32 / TICK_RATE as f32);
+    println!("estado: {:?}", game.state);
+    print!("{}", state);
+    if frames > 0 {
+        println!("fotogramas: {}", frames);
+    }
+
+    match &replay.check {
+        Some(expected) if expected.matches(&state) => {
+            println!("El estado final coincide bit a bit con la grabación");
+            ExitCode::SUCCESS
+        }
+        Some(expected) => {
+            println!("El estado final NO coincide con la grabación, que terminó así:");
+            print!("{}", expected);
+            ExitCode::FAILURE
+        }
+        None => ExitCode::SUCCESS,
+    }
+}
+
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(|s| s.as_str())
+}
@@ -0,0 +1,224 @@
+// Simulación sin ventana ni audio para pruebas automáticas: carga los
+// niveles, aplica un guion de entradas durante N ticks y escribe en la salida
+// estándar un resumen en JSON con los eventos del juego.
+//
+//     cargo run --bin simulate -- --level maze.txt --ticks 600 --script ruta.txt
+//
+// El guion tiene una entrada por línea, `<tick> <acción> [up] [xN]` o
+// `<tick> look <radianes>`; `xN` repite la pulsación N ticks seguidos:
+//
+//     0   forward x12   # avanzar 12 pasos
+//     12  turn_right
+//     20  look 0.25
+//
+// La partida empieza ya jugando el primer nivel y se detiene al ganar o perder.
+use std::process::ExitCode;
+
+use raycaster::audio::{AudioCommand, AudioManager, Bus};
+use raycaster::game::{Game, GameEvent, GameState, View, TICK_DT, TICK_RATE};
+use raycaster::input::{Action, Input};
+use raycaster::settings::Settings;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+
+    let mut levels: Vec<String> = args
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| *a == "--level")
+        .filter_map(|(i, _)| args.get(i + 1).cloned())
+        .collect();
+    if levels.is_empty() {
+        levels.push("maze.txt".to_string());
+    }
+    let ticks: u64 = match arg_value(&args, "--ticks").map(str::parse) {
+        Some(Ok(ticks)) => ticks,
+        Some(Err(_)) => return usage("--ticks espera un número"),
+        None => TICK_RATE as u64 * 60,
+    };
+    let seed: u64 = match arg_value(&args, "--seed").map(str::parse) {
+        Some(Ok(seed)) => seed,
+        Some(Err(_)) => return usage("--seed espera un número"),
+        None => 0,
+    };
+    let script = match arg_value(&args, "--script") {
+        Some(path) => match std::fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|t| parse_script(&t)) {
+            Ok(script) => script,
+            Err(err) => return usage(&format!("{}: {}", path, err)),
+        },
+        None => Vec::new(),
+    };
+    if let Some(missing) = levels.iter().find(|l| !std::path::Path::new(l).exists()) {
+        return usage(&format!("no se encuentra el nivel {}", missing));
+    }
+
+    let mut game = Game::new(levels, seed, Settings::default());
+    // Sin sonido, pero con registro de lo que se habría oído
+    let (mut audio, audio_log) = AudioManager::recording();
+    game.transition(GameState::Playing { view: View::TwoD });
+    game.drain_events();
+
+    let mut events = Vec::new();
+    let mut carrots = 0;
+    let mut footsteps = 0;
+    let mut levels_completed = 0;
+    let mut inputs = script.into_iter().peekable();
+    while game.ticks < ticks && !matches!(game.state, GameState::Won | GameState::Lost) {
+        let tick = game.ticks;
+        while let Some((_, input)) = inputs.next_if(|(t, _)| *t <= tick) {
+            game.apply(input);
+        }
+        game.tick();
+        audio.update(&game, TICK_DT);
+
+        for event in game.drain_events() {
+            audio.handle_event(&event, &game.world);
+            match event {
+                GameEvent::CarrotCollected { .. } => carrots += 1,
+                GameEvent::LevelCompleted { .. } => levels_completed += 1,
+                GameEvent::Footstep { .. } => {
+                    footsteps += 1;
+                    continue; // Solo afecta al audio
+                }
+                GameEvent::CatMeow { .. } => continue,
+                _ => {}
+            }
+            events.push(event_json(tick, &event));
+        }
+    }
+
+    let player = &game.world.player;
+    let cats: Vec<String> = game
+        .world
+        .cats
+        .iter()
+        .map(|cat| format!("{{\"x\": {}, \"y\": {}}}", cat.pos.x, cat.pos.y))
+        .collect();
+    println!("{{");
+    println!("  \"ticks\": {},", game.ticks);
+    println!("  \"final_state\": {},", json_string(&format!("{:?}", game.state)));
+    println!("  \"won\": {},", game.state == GameState::Won);
+    println!("  \"lost\": {},", game.state == GameState::Lost);
+    println!("  \"level\": {},", game.level);
+    println!("  \"levels_completed\": {},", levels_completed);
+    println!("  \"score\": {},", game.world.score);
+    println!("  \"health\": {},", game.world.health);
+    println!("  \"lives\": {},", game.world.lives);
+    println!("  \"run_ticks\": {},", game.run.ticks);
+    let splits: Vec<String> = game.run.splits.iter().map(u64::to_string).collect();
+    println!("  \"splits\": [{}],", splits.join(", "));
+    println!("  \"carrots_collected\": {},", carrots);
+    println!("  \"carrots_left\": {},", game.world.maze.iter().flatten().filter(|&&c| c == 'z').count());
+    println!("  \"player\": {{\"x\": {}, \"y\": {}, \"a\": {}}},", player.pos.x, player.pos.y, player.a);
+    println!("  \"cats\": [{}],", cats.join(", "));
+    let sounds: Vec<String> = audio_log
+        .borrow()
+        .iter()
+        .filter_map(|command| match command {
+            AudioCommand::PlayOnce { bus: Bus::Footsteps, .. } => None,
+            AudioCommand::PlayOnce { sound, .. } | AudioCommand::PlayAt { sound, .. } => Some(json_string(sound)),
+            _ => None,
+        })
+        .collect();
+    println!("  \"sounds\": [{}],", sounds.join(", "));
+    println!("  \"footsteps\": {},", footsteps);
+    println!("  \"events\": [");
+    for (i, event) in events.iter().enumerate() {
+        println!("    {}{}", event, if i + 1 < events.len() { "," } else { "" });
+    }
+    println!("  ]");
+    println!("}}");
+    ExitCode::SUCCESS
+}
+
+fn usage(error: &str) -> ExitCode {
+    eprintln!("Error: {}", error);
+    eprintln!("Uso: simulate [--level archivo]... [--ticks N] [--seed N] [--script guion]");
+    ExitCode::FAILURE
+}
+
+fn parse_script(text: &str) -> Result<Vec<(u64, Input)>, String> {
+    let mut script = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let err = || format!("línea {}: no se entiende `{}`", idx + 1, line);
+        let mut parts = line.split_whitespace();
+        let tick: u64 = parts.next().and_then(|t| t.parse().ok()).ok_or_else(err)?;
+        let name = parts.next().ok_or_else(err)?;
+
+        if name == "look" {
+            let delta = parts.next().and_then(|d| d.parse().ok()).ok_or_else(err)?;
+            script.push((tick, Input::Look(delta)));
+            continue;
+        }
+        let action = Action::from_name(name).ok_or_else(err)?;
+        let mut pressed = true;
+        let mut repeat = 1;
+        for part in parts {
+            match part {
+                "up" => pressed = false,
+                "down" => pressed = true,
+                _ => repeat = part.strip_prefix('x').and_then(|n| n.parse().ok()).ok_or_else(err)?,
+            }
+        }
+        for i in 0..repeat {
+            script.push((tick + i, Input::Key { action, pressed }));
+        }
+    }
+    // Orden estable: las entradas del mismo tick conservan el orden del guion
+    script.sort_by_key(|(tick, _)| *tick);
+    Ok(script)
+}
+
+fn event_json(tick: u64, event: &GameEvent) -> String {
+    let body = match event {
+        GameEvent::StateChanged { from, to } => format!(
+            "\"type\": \"state_changed\", \"from\": {}, \"to\": {}",
+            json_string(&format!("{:?}", from)),
+            json_string(&format!("{:?}", to))
+        ),
+        GameEvent::CarrotCollected { score } => format!("\"type\": \"carrot_collected\", \"score\": {}", score),
+        GameEvent::Hurt { health } => format!("\"type\": \"hurt\", \"health\": {}", health),
+        GameEvent::LifeLost { lives } => format!("\"type\": \"life_lost\", \"lives\": {}", lives),
+        GameEvent::Healed { health } => format!("\"type\": \"healed\", \"health\": {}", health),
+        GameEvent::KeyCollected(color) => format!("\"type\": \"key_collected\", \"color\": {}", json_string(color.name())),
+        GameEvent::DoorOpened { pos } => format!("\"type\": \"door_opened\", \"x\": {}, \"y\": {}", pos.x, pos.y),
+        GameEvent::DoorLocked(color) => format!("\"type\": \"door_locked\", \"color\": {}", json_string(color.name())),
+        GameEvent::LevelCompleted { level } => format!("\"type\": \"level_completed\", \"level\": {}", level),
+        GameEvent::Won => "\"type\": \"won\"".to_string(),
+        GameEvent::Lost => "\"type\": \"lost\"".to_string(),
+        GameEvent::CatMeow { pos } => format!("\"type\": \"cat_meow\", \"x\": {}, \"y\": {}", pos.x, pos.y),
+        GameEvent::Footstep { floor } => format!("\"type\": \"footstep\", \"floor\": {}", json_string(floor.name())),
+        GameEvent::SettingsChanged => "\"type\": \"settings_changed\"".to_string(),
+        GameEvent::Saved { slot } => format!("\"type\": \"saved\", \"slot\": {}", slot),
+        GameEvent::Loaded { slot } => format!("\"type\": \"loaded\", \"slot\": {}", slot),
+        GameEvent::SaveFailed { slot, error } => {
+            format!("\"type\": \"save_failed\", \"slot\": {}, \"error\": {}", slot, json_string(error))
+        }
+        GameEvent::NewRecord { rank } => format!("\"type\": \"new_record\", \"rank\": {}", rank),
+        GameEvent::Quit => "\"type\": \"quit\"".to_string(),
+    };
+    format!("{{\"tick\": {}, {}}}", tick, body)
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(|s| s.as_str())
+}
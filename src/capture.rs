@@ -0,0 +1,106 @@
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, ImageResult, RgbaImage};
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::scaling::{fit_rect, upscale, ScaleFilter};
+
+// Carpeta de las capturas hechas con la tecla
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+// Los GIF se reducen a este ancho como mucho: a tamaño completo pesan demasiado
+// y tardan mucho en cuantizarse
+const GIF_MAX_WIDTH: usize = 640;
+
+pub fn save_png(path: &Path, buffer: &[u8], width: usize, height: usize) -> ImageResult<()> {
+    image::save_buffer(path, &buffer[..width * height * 4], width as u32, height as u32, image::ColorType::Rgba8)
+}
+
+// Guarda la imagen en `dir` con un nombre del estilo
+// `captura-2024-05-01_18-30-12-345.png` (hora UTC)
+pub fn save_screenshot(dir: &Path, buffer: &[u8], width: usize, height: usize) -> ImageResult<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("captura-{}.png", timestamp(SystemTime::now())));
+    save_png(&path, buffer, width, height)?;
+    Ok(path)
+}
+
+// Fecha y hora UTC sin depender de ninguna biblioteca de fechas
+pub fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Días desde 1970-01-01 a fecha civil (algoritmo de Howard Hinnant)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}-{:03}",
+        year,
+        month,
+        day,
+        secs_of_day / 3_600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+// Graba una secuencia de fotogramas: PNG numerados en una carpeta o un GIF
+// animado si la ruta termina en `.gif`
+pub enum FrameCapture {
+    Png { dir: PathBuf, next: usize },
+    Gif { encoder: GifEncoder<BufWriter<File>>, size: Option<(usize, usize)>, scaled: Vec<u8> },
+}
+
+impl FrameCapture {
+    pub fn create(path: impl AsRef<Path>) -> ImageResult<Self> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gif")) {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            let mut encoder = GifEncoder::new_with_speed(BufWriter::new(File::create(path)?), 10);
+            encoder.set_repeat(Repeat::Infinite)?;
+            Ok(FrameCapture::Gif { encoder, size: None, scaled: Vec::new() })
+        } else {
+            fs::create_dir_all(path)?;
+            Ok(FrameCapture::Png { dir: path.to_path_buf(), next: 0 })
+        }
+    }
+
+    // `delay`: tiempo que se muestra el fotograma (solo cuenta en los GIF)
+    pub fn add_frame(&mut self, buffer: &[u8], width: usize, height: usize, delay: Duration) -> ImageResult<()> {
+        match self {
+            FrameCapture::Png { dir, next } => {
+                save_png(&dir.join(format!("frame_{:06}.png", next)), buffer, width, height)?;
+                *next += 1;
+                Ok(())
+            }
+            FrameCapture::Gif { encoder, size, scaled } => {
+                // Todos los fotogramas del GIF tienen el tamaño del primero; si la
+                // resolución cambia a mitad de la grabación se encaja con bandas negras
+                let (gif_w, gif_h) = *size.get_or_insert_with(|| {
+                    let (_, _, w, h) = fit_rect(width, height, GIF_MAX_WIDTH.min(width), height);
+                    (w, h)
+                });
+                scaled.resize(gif_w * gif_h * 4, 0);
+                upscale(buffer, width, height, scaled, gif_w, gif_h, ScaleFilter::Linear);
+
+                let image = RgbaImage::from_raw(gif_w as u32, gif_h as u32, scaled.clone())
+                    .expect("el buffer tiene el tamaño del fotograma");
+                encoder.encode_frame(Frame::from_parts(image, 0, 0, Delay::from_saturating_duration(delay)))
+            }
+        }
+    }
+}
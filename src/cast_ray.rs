@@ -1,24 +1,85 @@
+use nalgebra_glm::Vec2;
+
+use crate::door::{door_at, door_tile_at, is_door_tile, Door};
 use crate::framebuffer::Framebuffer;
+use crate::maze::is_floor;
 use crate::player::Player;
 
 pub struct Intersect {
     pub distance: f32,
     pub impact: char,
     pub tx: usize, // Nueva propiedad para la coordenada x de la textura
+    pub face: Face,
+    pub steps: u32, // Pasos que dio el rayo hasta chocar
+}
+
+// Lado de la celda que toca el rayo; el norte es hacia las filas de arriba
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Face {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Face {
+    pub fn name(self) -> &'static str {
+        match self {
+            Face::North => "norte",
+            Face::South => "sur",
+            Face::East => "este",
+            Face::West => "oeste",
+        }
+    }
+
+    // El borde de la celda (`col`, `row`) más cercano a `hit`
+    fn nearest(hit: Vec2, col: usize, row: usize, block_size: usize) -> Self {
+        let bs = block_size as f32;
+        let (u, v) = (hit.x - col as f32 * bs, hit.y - row as f32 * bs);
+        [(v, Face::North), (bs - v, Face::South), (u, Face::West), (bs - u, Face::East)]
+            .into_iter()
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or(Face::North, |(_, face)| face)
+    }
 }
 
 pub fn cast_ray(
     framebuffer: &mut Framebuffer,
-    maze: &Vec<Vec<char>>,
+    maze: &[Vec<char>],
+    doors: &[Door],
     player: &Player,
     a: f32,
     block_size: usize,
     draw_line: bool,
 ) -> Intersect {
+    let intersect = trace_ray(maze, doors, player, a, block_size);
+
+    if draw_line {
+        let color = [0xFF, 0xDD, 0xDD, 0xFF];
+        let end_x = player.pos.x + intersect.distance * a.cos();
+        let end_y = player.pos.y + intersect.distance * a.sin();
+        framebuffer.draw_line(
+            player.pos.x as isize,
+            player.pos.y as isize,
+            end_x as isize,
+            end_y as isize,
+            color,
+        );
+    }
+
+    intersect
+}
+
+// Recorre el rayo sin tocar el framebuffer, para poder lanzarlo desde varios hilos.
+// Las puertas se cruzan hasta la hoja del centro de la celda; una puerta que no
+// está en `doors` se toma como cerrada
+pub fn trace_ray(maze: &[Vec<char>], doors: &[Door], player: &Player, a: f32, block_size: usize) -> Intersect {
     let mut d = 0.0;
-    let color = [0xFF, 0xDD, 0xDD, 0xFF];
+    let dir = Vec2::new(a.cos(), a.sin());
+    let mut steps = 0;
 
     loop {
+        steps += 1;
         let cos = d * a.cos();
         let sin = d * a.sin();
         let x = (player.pos.x + cos) as usize;
@@ -27,18 +88,33 @@ pub fn cast_ray(
         let i = x / block_size;
         let j = y / block_size;
 
-        if maze[j][i] != ' ' {
-            return Intersect {
+        let tile = maze[j][i];
+        if is_door_tile(tile) {
+            let hit = match door_at(doors, i, j) {
+                Some(door) => door.hit(player.pos, dir, block_size).map(|hit| (hit, door.across_x)),
+                None => door_tile_at(maze, i, j)
+                    .and_then(|door| door.hit(player.pos, dir, block_size).map(|hit| (hit, door.across_x))),
+            };
+            if let Some(((distance, tx), across_x)) = hit {
+                // La hoja está en el centro de la celda: se le ve la cara de donde viene el rayo
+                let face = match (across_x, dir.y > 0.0, dir.x > 0.0) {
+                    (true, true, _) => Face::North,
+                    (true, false, _) => Face::South,
+                    (false, _, true) => Face::West,
+                    (false, _, false) => Face::East,
+                };
+                break Intersect { distance, impact: tile, tx, face, steps };
+            }
+        } else if !is_floor(tile) {
+            break Intersect {
                 distance: d,
                 impact: maze[j][i],
                 tx: x % block_size,
+                face: Face::nearest(player.pos + dir * d, i, j, block_size),
+                steps,
             };
         }
 
-        if draw_line {
-            framebuffer.point(x, y, color);
-        }
-
         d += 5.0;
     }
 }
@@ -0,0 +1,252 @@
+use std::fmt;
+
+use crate::framebuffer::Framebuffer;
+use crate::settings::SETTING_KEYS;
+use crate::text::TextStyle;
+
+// Órdenes que se ejecutan al arrancar el juego, si el archivo existe
+pub const CONSOLE_SCRIPT: &str = "autoexec.cfg";
+
+// Líneas de salida y órdenes del historial que se guardan
+const OUTPUT_LINES: usize = 200;
+const HISTORY_LINES: usize = 50;
+// Parte de la pantalla que ocupa la consola desplegada
+const CONSOLE_HEIGHT: f32 = 0.45;
+const BACKDROP_COLOR: [u8; 4] = [0x08, 0x08, 0x10, 0xFF];
+const BACKDROP_ALPHA: f32 = 0.8;
+const INPUT_COLOR: [u8; 4] = [0xFF, 0xF0, 0x60, 0xFF];
+const OUTPUT_COLOR: [u8; 4] = [0xDD, 0xDD, 0xDD, 0xFF];
+
+// Nombre, argumentos y qué hace cada orden, para `help` y el autocompletado
+pub const COMMANDS: [(&str, &str, &str); 13] = [
+    ("help", "", "lista las órdenes"),
+    ("tp", "columna fila", "lleva al jugador al centro de esa celda"),
+    ("fov", "grados", "cambia el campo de visión"),
+    ("noclip", "", "atravesar paredes (activa o desactiva)"),
+    ("god", "", "no recibir daño (activa o desactiva)"),
+    ("score", "n", "suma n puntos"),
+    ("reload", "", "vuelve a cargar el nivel desde el principio"),
+    ("spawn", "[columna fila]", "pone un gato delante del jugador o en esa celda"),
+    ("fog", "", "activa o desactiva la niebla"),
+    ("minimap", "", "cambia el modo del minimapa"),
+    ("set", "clave valor", "cambia una opción o una variable del nivel"),
+    ("exec", "archivo", "ejecuta las órdenes de un archivo"),
+    ("clear", "", "borra la consola"),
+];
+
+// Variables del nivel que acepta `set` además de las opciones
+pub const WORLD_VARS: [&str; 2] = ["health", "lives"];
+
+// Una orden ya leída, con sus argumentos
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Help,
+    Teleport { col: usize, row: usize },
+    Fov(f32),
+    Noclip,
+    God,
+    Score(u32),
+    Reload,
+    Spawn(Option<(usize, usize)>),
+    Fog,
+    Minimap,
+    Set { key: String, value: String },
+    Exec(String),
+    Clear,
+}
+
+impl Command {
+    // Cambia la partida de una forma que no se puede conseguir jugando:
+    // deja de contar para la tabla de récords
+    pub fn is_cheat(&self) -> bool {
+        match self {
+            Command::Teleport { .. } | Command::Noclip | Command::God | Command::Score(_) | Command::Reload => true,
+            Command::Set { key, .. } => WORLD_VARS.contains(&key.as_str()),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConsoleError {
+    Unknown(String),
+    Usage(&'static str, &'static str), // Orden y argumentos que espera
+}
+
+impl fmt::Display for ConsoleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsoleError::Unknown(name) => write!(f, "Orden desconocida: {} (prueba con help)", name),
+            ConsoleError::Usage(name, args) => write!(f, "Uso: {} {}", name, args),
+        }
+    }
+}
+
+// Lee una línea de la consola o del archivo de arranque. Las líneas vacías y
+// las que empiezan por `#` no son órdenes
+pub fn parse_command(line: &str) -> Result<Option<Command>, ConsoleError> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next().filter(|w| !w.starts_with('#')) else { return Ok(None) };
+    let args: Vec<&str> = words.collect();
+    let Some(&(name, usage, _)) = COMMANDS.iter().find(|(n, _, _)| *n == name) else {
+        return Err(ConsoleError::Unknown(name.to_string()));
+    };
+    let usage_error = || ConsoleError::Usage(name, usage);
+    let cell = |col: &str, row: &str| Some((col.parse().ok()?, row.parse().ok()?));
+    let command = match (name, args.as_slice()) {
+        ("help", []) => Command::Help,
+        ("tp", [col, row]) => cell(col, row).map(|(col, row)| Command::Teleport { col, row }).ok_or_else(usage_error)?,
+        ("fov", [degrees]) => Command::Fov(degrees.parse().map_err(|_| usage_error())?),
+        ("noclip", []) => Command::Noclip,
+        ("god", []) => Command::God,
+        ("score", [n]) => Command::Score(n.parse().map_err(|_| usage_error())?),
+        ("reload", []) => Command::Reload,
+        ("spawn", []) => Command::Spawn(None),
+        ("spawn", [col, row]) => Command::Spawn(Some(cell(col, row).ok_or_else(usage_error)?)),
+        ("fog", []) => Command::Fog,
+        ("minimap", []) => Command::Minimap,
+        ("set", [key, value]) => Command::Set { key: key.to_string(), value: value.to_string() },
+        ("exec", [path]) => Command::Exec(path.to_string()),
+        ("clear", []) => Command::Clear,
+        _ => return Err(usage_error()),
+    };
+    Ok(Some(command))
+}
+
+// Consola desplegable: la línea que se escribe, lo que han ido respondiendo
+// las órdenes y las órdenes anteriores
+#[derive(Clone, Debug, Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub output: Vec<String>,
+    history: Vec<String>,
+    browsing: Option<usize>, // Posición en `history` mientras se recorre con las flechas
+    toggle_char: bool,       // La tecla de la consola sigue pulsada y su carácter no ha llegado
+}
+
+impl Console {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.browsing = None;
+        self.toggle_char = true;
+    }
+
+    // La tecla que abre o cierra la consola también llega como carácter justo
+    // después: `true` si el carácter que llega ahora es ese y no se escribe
+    pub fn skip_toggle_char(&mut self) -> bool {
+        std::mem::take(&mut self.toggle_char)
+    }
+
+    // Al soltar la tecla ya no llega su carácter (con teclas muertas no llega)
+    pub fn toggle_released(&mut self) {
+        self.toggle_char = false;
+    }
+
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.output.push(line.into());
+        if self.output.len() > OUTPUT_LINES {
+            self.output.remove(0);
+        }
+    }
+
+    // Carácter escrito
+    pub fn type_char(&mut self, c: char) {
+        match c {
+            '\u{8}' | '\u{7f}' => {
+                self.input.pop();
+            }
+            '\t' => self.complete(),
+            c if !c.is_control() => self.input.push(c),
+            _ => {}
+        }
+        self.browsing = None;
+    }
+
+    // Devuelve la línea escrita, la guarda en el historial y deja la entrada vacía
+    pub fn submit(&mut self) -> String {
+        let line = std::mem::take(&mut self.input);
+        self.browsing = None;
+        if !line.trim().is_empty() && self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+            if self.history.len() > HISTORY_LINES {
+                self.history.remove(0);
+            }
+        }
+        line
+    }
+
+    // Orden anterior del historial (flecha arriba)
+    pub fn history_previous(&mut self) {
+        if self.history.is_empty() {
+            return;
+        }
+        let i = self.browsing.map_or(self.history.len() - 1, |i| i.saturating_sub(1));
+        self.browsing = Some(i);
+        self.input = self.history[i].clone();
+    }
+
+    // Orden siguiente (flecha abajo); después de la última la entrada queda vacía
+    pub fn history_next(&mut self) {
+        let Some(i) = self.browsing else { return };
+        if i + 1 < self.history.len() {
+            self.browsing = Some(i + 1);
+            self.input = self.history[i + 1].clone();
+        } else {
+            self.browsing = None;
+            self.input.clear();
+        }
+    }
+
+    // Completa el nombre de la orden o, tras `set`, la clave. Si hay varias
+    // opciones se completa lo que tienen en común y se listan
+    fn complete(&mut self) {
+        let (prefix, word, candidates): (&str, &str, Vec<&str>) = match self.input.split_once(' ') {
+            None => ("", self.input.as_str(), COMMANDS.iter().map(|(name, _, _)| *name).collect()),
+            Some(("set", key)) if !key.contains(' ') => {
+                ("set ", key, SETTING_KEYS.iter().chain(WORLD_VARS.iter()).copied().collect())
+            }
+            _ => return,
+        };
+        let matches: Vec<&str> = candidates.into_iter().filter(|c| c.starts_with(word)).collect();
+        let Some(first) = matches.first() else { return };
+        if matches.len() == 1 {
+            self.input = format!("{}{} ", prefix, first);
+            return;
+        }
+        let common = matches
+            .iter()
+            .map(|m| first.bytes().zip(m.bytes()).take_while(|(a, b)| a == b).count())
+            .min()
+            .unwrap_or(0);
+        self.input = format!("{}{}", prefix, &first[..common]);
+        self.print(matches.join("  "));
+    }
+}
+
+// La consola sobre la parte de arriba de la pantalla: la salida más reciente
+// justo encima de la línea que se escribe
+pub fn render_console(framebuffer: &mut Framebuffer, console: &Console, ui_scale: f32) {
+    if !console.open {
+        return;
+    }
+    let width = framebuffer.get_width();
+    let height = (framebuffer.get_height() as f32 * CONSOLE_HEIGHT) as usize;
+    let margin = (10.0 * ui_scale) as isize;
+    let line_height = (24.0 * ui_scale).max(8.0) as isize;
+    framebuffer.blend_rect(0, 0, width, height, BACKDROP_COLOR, BACKDROP_ALPHA);
+    framebuffer.draw_line(0, height as isize, width as isize - 1, height as isize, INPUT_COLOR);
+
+    let mut y = height as isize - margin - line_height;
+    let input_style = TextStyle::new(20.0 * ui_scale).color(INPUT_COLOR);
+    framebuffer.draw_text_styled(&format!("> {}_", console.input), margin, y, &input_style);
+
+    let output_style = TextStyle::new(20.0 * ui_scale).color(OUTPUT_COLOR);
+    for line in console.output.iter().rev() {
+        y -= line_height;
+        if y < margin / 2 {
+            break;
+        }
+        framebuffer.draw_text_styled(line, margin, y, &output_style);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::framebuffer::Framebuffer;
+use crate::maze::tile_name;
+use crate::profiler::{FrameStats, FRAME_HISTORY, PHASES};
+use crate::render::has_line_of_sight;
+use crate::text::TextStyle;
+use crate::world::World;
+
+const PANEL_COLOR: [u8; 4] = [0x00, 0x00, 0x00, 0xFF];
+const PANEL_ALPHA: f32 = 0.65;
+const GRAPH_COLOR: [u8; 4] = [0x40, 0xD0, 0x60, 0xFF];
+const SLOW_COLOR: [u8; 4] = [0xE0, 0x40, 0x30, 0xFF];
+const BUDGET_COLOR: [u8; 4] = [0xFF, 0xFF, 0x80, 0xFF];
+const PHASE_COLOR: [u8; 4] = [0x60, 0xA0, 0xFF, 0xFF];
+const LABEL_COLOR: [u8; 4] = [0xDD, 0xDD, 0xDD, 0xFF];
+// Milisegundos de un fotograma a 60 FPS: la línea de referencia de la gráfica
+const FRAME_BUDGET_MS: f32 = 1000.0 / 60.0;
+// Lo que cabe de alto en la gráfica
+const GRAPH_MAX_MS: f32 = FRAME_BUDGET_MS * 3.0;
+
+// Panel de desarrollo a la derecha, debajo de los puntos: gráfica de tiempos
+// por fotograma, lo que tardó cada fase, rayos, jugador, celda y gatos
+pub fn render_debug_overlay(framebuffer: &mut Framebuffer, world: &World, stats: &FrameStats, ui_scale: f32) {
+    let width = framebuffer.get_width();
+    let margin = (10.0 * ui_scale) as isize;
+    let line_height = (22.0 * ui_scale).max(8.0) as isize;
+    let panel_w = (width as f32 * 0.36) as usize;
+    let left = width as isize - panel_w as isize - margin;
+    let top = margin + (50.0 * ui_scale) as isize;
+    let inner_w = panel_w as isize - margin * 2;
+    let graph_h = (80.0 * ui_scale) as isize;
+
+    let mut lines = Vec::new();
+    let bs = world.block_size as f32;
+    let player = &world.player;
+    lines.push(format!("Rayos: {} · pasos medios: {:.1}", stats.rays, stats.average_ray_steps()));
+    lines.push(format!(
+        "Jugador: {:.1}, {:.1} · {:.1}°",
+        player.pos.x / bs,
+        player.pos.y / bs,
+        player.a.to_degrees().rem_euclid(360.0)
+    ));
+    let (col, row) = ((player.pos.x / bs) as usize, (player.pos.y / bs) as usize);
+    if let Some(&tile) = world.maze.get(row).and_then(|line| line.get(col)) {
+        // Con el código del carácter: la fuente no tiene todos los símbolos
+        lines.push(format!("Celda {}, {}: {} (0x{:02X})", col, row, tile_name(tile), tile as u32));
+    }
+    for (i, cat) in world.cats.iter().enumerate() {
+        let heading = if cat.direction > 0.0 { "ida" } else { "vuelta" };
+        let distance = (cat.pos - player.pos).magnitude() / bs;
+        let seen = has_line_of_sight(&player.pos, &cat.pos, &world.maze, world.block_size);
+        lines.push(format!(
+            "Gato {}: {:.1}, {:.1} · {} · {:.1} bloques{}",
+            i + 1,
+            cat.pos.x / bs,
+            cat.pos.y / bs,
+            heading,
+            distance,
+            if seen { " · a la vista" } else { "" }
+        ));
+    }
+
+    let phases_h = line_height * PHASES.len() as isize;
+    let panel_h = margin * 4 + line_height + graph_h + phases_h + line_height * lines.len() as isize;
+    framebuffer.blend_rect(left, top, panel_w, panel_h as usize, PANEL_COLOR, PANEL_ALPHA);
+
+    let style = TextStyle::new(18.0 * ui_scale).color(LABEL_COLOR);
+    let (x, mut y) = (left + margin, top + margin);
+    let last = stats.frame_times.back().copied().unwrap_or(0.0);
+    let average = stats.frame_times.iter().sum::<f32>() / stats.frame_times.len().max(1) as f32;
+    framebuffer.draw_text_styled(&format!("Fotograma: {:.1} ms · media {:.1} ms", last, average), x, y, &style);
+    y += line_height;
+
+    // Una barra por fotograma, el más reciente a la derecha
+    let bar_w = (inner_w as f32 / FRAME_HISTORY as f32).max(1.0);
+    let graph_bottom = y + graph_h;
+    let skip = FRAME_HISTORY.saturating_sub(stats.frame_times.len());
+    for (i, &ms) in stats.frame_times.iter().enumerate() {
+        let bar_h = ((ms / GRAPH_MAX_MS).min(1.0) * graph_h as f32).max(1.0) as usize;
+        let bar_x = x + ((skip + i) as f32 * bar_w) as isize;
+        let color = if ms > FRAME_BUDGET_MS { SLOW_COLOR } else { GRAPH_COLOR };
+        framebuffer.fill_rect(bar_x, graph_bottom - bar_h as isize, bar_w.ceil() as usize, bar_h, color);
+    }
+    let budget_y = graph_bottom - (FRAME_BUDGET_MS / GRAPH_MAX_MS * graph_h as f32) as isize;
+    framebuffer.draw_line(x, budget_y, x + inner_w, budget_y, BUDGET_COLOR);
+    framebuffer.draw_rect_outline(x, y, inner_w as usize, graph_h as usize, LABEL_COLOR);
+    y = graph_bottom + margin;
+
+    // Cada fase con su tiempo y una barra en proporción al fotograma
+    let total: f32 = stats.phases.iter().sum::<f32>().max(f32::EPSILON);
+    let bar_left = x + inner_w / 2;
+    for (phase, &ms) in PHASES.iter().zip(&stats.phases) {
+        framebuffer.draw_text_styled(&format!("{}: {:.2} ms", phase.label(), ms), x, y, &style);
+        let bar = ((ms / total) * (inner_w / 2) as f32) as usize;
+        framebuffer.fill_rect(bar_left, y + line_height / 4, bar, (line_height / 2) as usize, PHASE_COLOR);
+        y += line_height;
+    }
+    y += margin;
+
+    for line in &lines {
+        framebuffer.draw_text_styled(line, x, y, &style);
+        y += line_height;
+    }
+}
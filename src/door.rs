@@ -0,0 +1,132 @@
+use nalgebra_glm::Vec2;
+
+// Segundos que tarda una puerta en abrirse del todo
+pub const DOOR_OPEN_TIME: f32 = 0.8;
+
+// Color de una llave y de las puertas que abre. En el laberinto la llave es la
+// letra en minúscula y la puerta cerrada con llave la misma en mayúscula
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyColor {
+    Red,
+    Blue,
+    Yellow,
+}
+
+pub const KEY_COLORS: [KeyColor; 3] = [KeyColor::Red, KeyColor::Blue, KeyColor::Yellow];
+
+impl KeyColor {
+    pub fn key_tile(self) -> char {
+        match self {
+            KeyColor::Red => 'r',
+            KeyColor::Blue => 'b',
+            KeyColor::Yellow => 'y',
+        }
+    }
+
+    pub fn door_tile(self) -> char {
+        self.key_tile().to_ascii_uppercase()
+    }
+
+    pub fn from_key_tile(tile: char) -> Option<Self> {
+        KEY_COLORS.into_iter().find(|c| c.key_tile() == tile)
+    }
+
+    pub fn from_door_tile(tile: char) -> Option<Self> {
+        KEY_COLORS.into_iter().find(|c| c.door_tile() == tile)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyColor::Red => "roja",
+            KeyColor::Blue => "azul",
+            KeyColor::Yellow => "amarilla",
+        }
+    }
+
+    pub fn rgba(self) -> [u8; 4] {
+        match self {
+            KeyColor::Red => [0xD0, 0x20, 0x20, 0xFF],
+            KeyColor::Blue => [0x20, 0x50, 0xD0, 0xFF],
+            KeyColor::Yellow => [0xE8, 0xC0, 0x10, 0xFF],
+        }
+    }
+}
+
+// 'd' es una puerta normal; las mayúsculas de las llaves, puertas con llave
+pub fn is_door_tile(tile: char) -> bool {
+    tile == 'd' || KeyColor::from_door_tile(tile).is_some()
+}
+
+fn is_wall_or_door(tile: char) -> bool {
+    matches!(tile, '+' | '-' | '|') || is_door_tile(tile)
+}
+
+// Puerta corredera: una hoja fina en el centro de la celda que se desliza
+// hacia un lado al abrirse
+#[derive(Clone, Debug, PartialEq)]
+pub struct Door {
+    pub col: usize,
+    pub row: usize,
+    pub lock: Option<KeyColor>,
+    pub across_x: bool, // La hoja va de izquierda a derecha (pasillo norte-sur)
+    pub open: f32,      // 0 = cerrada, 1 = abierta del todo
+    pub opening: bool,
+}
+
+impl Door {
+    pub fn center(&self, block_size: usize) -> Vec2 {
+        Vec2::new((self.col as f32 + 0.5) * block_size as f32, (self.row as f32 + 0.5) * block_size as f32)
+    }
+
+    // Distancia desde `origin` a lo largo de `dir` hasta la hoja, si el rayo
+    // la toca, y la coordenada de textura en píxeles
+    pub fn hit(&self, origin: Vec2, dir: Vec2, block_size: usize) -> Option<(f32, usize)> {
+        let bs = block_size as f32;
+        let (along_origin, along_dir, across_origin, across_dir, cell_start) = if self.across_x {
+            (origin.y, dir.y, origin.x, dir.x, self.col as f32 * bs)
+        } else {
+            (origin.x, dir.x, origin.y, dir.y, self.row as f32 * bs)
+        };
+        if along_dir.abs() < f32::EPSILON {
+            return None;
+        }
+        let plane = if self.across_x { (self.row as f32 + 0.5) * bs } else { (self.col as f32 + 0.5) * bs };
+        let t = (plane - along_origin) / along_dir;
+        if t < 0.0 {
+            return None;
+        }
+        // La hoja se esconde por el lado del principio de la celda
+        let local = across_origin + across_dir * t - cell_start - self.open * bs;
+        (local >= 0.0 && local < bs - self.open * bs).then_some((t, local as usize))
+    }
+}
+
+// Puerta cerrada de la celda, con la orientación que marcan sus vecinas
+pub fn door_tile_at(maze: &[Vec<char>], col: usize, row: usize) -> Option<Door> {
+    let line = &maze[row];
+    let tile = line[col];
+    if !is_door_tile(tile) {
+        return None;
+    }
+    let left = col.checked_sub(1).map(|c| line[c]).unwrap_or('+');
+    let right = line.get(col + 1).copied().unwrap_or('+');
+    Some(Door {
+        col,
+        row,
+        lock: KeyColor::from_door_tile(tile),
+        across_x: is_wall_or_door(left) && is_wall_or_door(right),
+        open: 0.0,
+        opening: false,
+    })
+}
+
+// Todas las puertas del laberinto, cerradas
+pub fn find_doors(maze: &[Vec<char>]) -> Vec<Door> {
+    (0..maze.len())
+        .flat_map(|row| (0..maze[row].len()).filter_map(move |col| door_tile_at(maze, col, row)))
+        .collect()
+}
+
+pub fn door_at(doors: &[Door], col: usize, row: usize) -> Option<&Door> {
+    doors.iter().find(|d| d.col == col && d.row == row)
+}
@@ -1,4 +1,4 @@
-use rusttype::{Font, Scale};
+use crate::text::{TextStyle, TEXT};
 
 pub struct Framebuffer<'a> {
     pub width: usize,
@@ -12,28 +12,216 @@ impl<'a> Framebuffer<'a> {
     }
 
     pub fn point(&mut self, x: usize, y: usize, color: [u8; 4]) {
-        let index = (y * self.width + x) * 4;
-        if index + 3 < self.buffer.len() {
-            self.buffer[index..index + 4].copy_from_slice(&color);
+        if x < self.width && y < self.height {
+            let index = (y * self.width + x) * 4;
+            if index + 3 < self.buffer.len() {
+                self.buffer[index..index + 4].copy_from_slice(&color);
+            }
+        }
+    }
+
+    // Igual que `point` pero con coordenadas con signo: lo que cae fuera se recorta
+    pub fn set_pixel(&mut self, x: isize, y: isize, color: [u8; 4]) {
+        if x >= 0 && y >= 0 {
+            self.point(x as usize, y as usize, color);
+        }
+    }
+
+    // Mezcla `color` con el píxel actual según `alpha` (0.0 - 1.0)
+    pub fn blend_pixel(&mut self, x: isize, y: isize, color: [u8; 4], alpha: f32) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        let index = (y as usize * self.width + x as usize) * 4;
+        if index + 3 >= self.buffer.len() {
+            return;
         }
+        let alpha = alpha.clamp(0.0, 1.0);
+        for (dst, &src) in self.buffer[index..index + 3].iter_mut().zip(&color[..3]) {
+            *dst = (*dst as f32 + (src as f32 - *dst as f32) * alpha) as u8;
+        }
+        self.buffer[index + 3] = 0xFF;
     }
 
     pub fn clear(&mut self, color: [u8; 4]) {
-        for y in 0..self.height {
-            for x in 0..self.width {
-                self.point(x, y, color);
+        let len = (self.width * self.height * 4).min(self.buffer.len());
+        for pixel in self.buffer[..len].chunks_exact_mut(4) {
+            pixel.copy_from_slice(&color);
+        }
+    }
+
+    // Copia una imagen RGBA de `width` x `height` con la esquina en (x, y)
+    pub fn blit(&mut self, src: &[u8], width: usize, height: usize, x: usize, y: usize) {
+        let visible = width.min(self.width.saturating_sub(x));
+        for row in 0..height.min(self.height.saturating_sub(y)) {
+            let dst = ((y + row) * self.width + x) * 4;
+            let from = row * width * 4;
+            self.buffer[dst..dst + visible * 4].copy_from_slice(&src[from..from + visible * 4]);
+        }
+    }
+
+    // Línea horizontal de x0 a x1 (inclusive), recortada a la pantalla
+    pub fn hline(&mut self, x0: isize, x1: isize, y: isize, color: [u8; 4]) {
+        let (x0, x1) = if x0 <= x1 { (x0, x1) } else { (x1, x0) };
+        let (Some((start, end)), Some((y, _))) = (clip_span(x0, x1, self.width), clip_span(y, y, self.height)) else {
+            return;
+        };
+        let row = y * self.width;
+        if let Some(span) = self.buffer.get_mut((row + start) * 4..(row + end + 1) * 4) {
+            for pixel in span.chunks_exact_mut(4) {
+                pixel.copy_from_slice(&color);
             }
         }
     }
 
+    // Línea vertical de y0 a y1 (inclusive), recortada a la pantalla
+    pub fn vline(&mut self, x: isize, y0: isize, y1: isize, color: [u8; 4]) {
+        let (y0, y1) = if y0 <= y1 { (y0, y1) } else { (y1, y0) };
+        let (Some((x, _)), Some((start, end))) = (clip_span(x, x, self.width), clip_span(y0, y1, self.height)) else {
+            return;
+        };
+        for y in start..=end {
+            self.point(x, y, color);
+        }
+    }
+
     pub fn draw_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: [u8; 4]) {
-        for j in 0..height {
-            for i in 0..width {
-                self.point(x + i, y + j, color);
+        self.fill_rect(x as isize, y as isize, width, height, color);
+    }
+
+    // Rectángulo relleno con coordenadas con signo, recortado a la pantalla
+    pub fn fill_rect(&mut self, x: isize, y: isize, width: usize, height: usize, color: [u8; 4]) {
+        let Some((top, bottom)) = clip_span(y, y + height as isize - 1, self.height) else { return };
+        for row in top..=bottom {
+            self.hline(x, x + width as isize - 1, row as isize, color);
+        }
+    }
+
+    // Rectángulo semitransparente (para capas sobre la escena)
+    pub fn blend_rect(&mut self, x: isize, y: isize, width: usize, height: usize, color: [u8; 4], alpha: f32) {
+        let rows = clip_span(y, y + height as isize - 1, self.height);
+        let cols = clip_span(x, x + width as isize - 1, self.width);
+        let (Some((top, bottom)), Some((left, right))) = (rows, cols) else { return };
+        for j in top..=bottom {
+            for i in left..=right {
+                self.blend_pixel(i as isize, j as isize, color, alpha);
             }
         }
     }
 
+    // Solo el borde del rectángulo
+    pub fn draw_rect_outline(&mut self, x: isize, y: isize, width: usize, height: usize, color: [u8; 4]) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let right = x + width as isize - 1;
+        let bottom = y + height as isize - 1;
+        self.hline(x, right, y, color);
+        self.hline(x, right, bottom, color);
+        self.vline(x, y, bottom, color);
+        self.vline(right, y, bottom, color);
+    }
+
+    // Línea de Bresenham entre dos puntos (ambos incluidos), recortada antes a
+    // la pantalla para no recorrer lo que queda fuera
+    pub fn draw_line(&mut self, x0: isize, y0: isize, x1: isize, y1: isize, color: [u8; 4]) {
+        let (right, bottom) = (self.width as f64 - 1.0, self.height as f64 - 1.0);
+        let points = [x0, y0, x1, y1].map(|v| v as f64);
+        let Some(clipped) = clip_line(points, [0.0, 0.0, right, bottom]) else { return };
+        let [x0, y0, x1, y1] = clipped.map(|v| v.round() as isize);
+        if y0 == y1 {
+            return self.hline(x0, x1, y0, color);
+        }
+        if x0 == x1 {
+            return self.vline(x0, y0, y1, color);
+        }
+
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        let (mut x, mut y) = (x0, y0);
+
+        loop {
+            self.set_pixel(x, y, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    // Línea suavizada (algoritmo de Xiaolin Wu), recortada a la pantalla más
+    // un píxel por lado, que es lo que llega a pintar el suavizado
+    pub fn draw_line_aa(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: [u8; 4]) {
+        let (right, bottom) = (self.width as f64, self.height as f64);
+        let points = [x0, y0, x1, y1].map(f64::from);
+        let Some(clipped) = clip_line(points, [-1.0, -1.0, right, bottom]) else { return };
+        let [x0, y0, x1, y1] = clipped.map(|v| v as f32);
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        let (mut x0, mut y0, mut x1, mut y1) = if steep { (y0, x0, y1, x1) } else { (x0, y0, x1, y1) };
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
+        let plot = |fb: &mut Self, a: isize, b: isize, alpha: f32| {
+            if steep {
+                fb.blend_pixel(b, a, color, alpha);
+            } else {
+                fb.blend_pixel(a, b, color, alpha);
+            }
+        };
+
+        let start = x0.round() as isize;
+        let end = x1.round() as isize;
+        let mut y = y0 + gradient * (start as f32 - x0);
+        for x in start..=end {
+            let base = y.floor();
+            let frac = y - base;
+            plot(self, x, base as isize, 1.0 - frac);
+            plot(self, x, base as isize + 1, frac);
+            y += gradient;
+        }
+    }
+
+    // Contorno de un círculo (algoritmo del punto medio)
+    pub fn draw_circle(&mut self, cx: isize, cy: isize, radius: isize, color: [u8; 4]) {
+        let (mut x, mut y) = (radius, 0);
+        let mut err = 1 - radius;
+        while x >= y {
+            for (px, py) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+                self.set_pixel(cx + px, cy + py, color);
+            }
+            y += 1;
+            if err < 0 {
+                err += 2 * y + 1;
+            } else {
+                x -= 1;
+                err += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    // Círculo relleno con líneas horizontales
+    pub fn fill_circle(&mut self, cx: isize, cy: isize, radius: isize, color: [u8; 4]) {
+        for dy in -radius..=radius {
+            let half = ((radius * radius - dy * dy) as f32).sqrt() as isize;
+            self.hline(cx - half, cx + half, cy + dy, color);
+        }
+    }
+
     pub fn get_width(&self) -> usize {
         self.width
     }
@@ -50,28 +238,48 @@ impl<'a> Framebuffer<'a> {
         self.draw_rect(0, self.height / 2, self.width, self.height / 2, ground_color);
     }
 
-    // Método para renderizar texto en el framebuffer
+    // Método para renderizar texto en el framebuffer (blanco, alineado a la izquierda)
     pub fn draw_text(&mut self, text: &str, x: usize, y: usize, scale: f32) {
-        let font_data = include_bytes!("../assets/Typold-Book500.ttf");
-        let font = Font::try_from_bytes(font_data as &[u8]).expect("Error cargando la fuente");
-        
-        let scale = Scale::uniform(scale);
-        let v_metrics = font.v_metrics(scale);
-        let offset = rusttype::point(x as f32, y as f32 + v_metrics.ascent);
-
-        let glyphs: Vec<_> = font.layout(text, scale, offset).collect();
-
-        for glyph in glyphs {
-            if let Some(bounding_box) = glyph.pixel_bounding_box() {
-                glyph.draw(|gx, gy, v| {
-                    let x = (gx as i32 + bounding_box.min.x) as usize;
-                    let y = (gy as i32 + bounding_box.min.y) as usize;
-                    if x < self.width && y < self.height {
-                        let intensity = (v * 255.0) as u8;
-                        self.point(x, y, [intensity, intensity, intensity, 255]);
-                    }
-                });
+        TEXT.draw(self, text, x as isize, y as isize, &TextStyle::new(scale));
+    }
+
+    // Texto con color, alineación, sombra o ajuste de línea
+    pub fn draw_text_styled(&mut self, text: &str, x: isize, y: isize, style: &TextStyle) {
+        TEXT.draw(self, text, x, y, style);
+    }
+}
+
+// Tramo del segmento `[x0, y0, x1, y1]` dentro del rectángulo
+// `[izquierda, arriba, derecha, abajo]` (Liang–Barsky), o `None` si no toca
+fn clip_line([x0, y0, x1, y1]: [f64; 4], [left, top, right, bottom]: [f64; 4]) -> Option<[f64; 4]> {
+    if left > right || top > bottom || [x0, y0, x1, y1].iter().any(|v| !v.is_finite()) {
+        return None;
+    }
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    let (mut enter, mut leave) = (0.0f64, 1.0f64);
+    for (p, q) in [(-dx, x0 - left), (dx, right - x0), (-dy, y0 - top), (dy, bottom - y0)] {
+        if p == 0.0 {
+            // Paralelo a este borde: o siempre dentro o siempre fuera
+            if q < 0.0 {
+                return None;
             }
+            continue;
+        }
+        let t = q / p;
+        if p < 0.0 {
+            enter = enter.max(t);
+        } else {
+            leave = leave.min(t);
+        }
+        if enter > leave {
+            return None;
         }
     }
+    Some([x0 + enter * dx, y0 + enter * dy, x0 + leave * dx, y0 + leave * dy])
+}
+
+// Parte de `start..=end` que cae en `0..limit`, o `None` si no queda nada
+fn clip_span(start: isize, end: isize, limit: usize) -> Option<(usize, usize)> {
+    let (start, end) = (start.max(0), end.min(limit as isize - 1));
+    (start <= end).then_some((start as usize, end as usize))
 }
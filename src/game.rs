@@ -0,0 +1,1523 @@
+use crate::automap::{render_automap, Automap, AUTOMAP_HINT};
+use crate::console::{parse_command, render_console, Command, Console, COMMANDS};
+use crate::debug_overlay::render_debug_overlay;
+use crate::door::{is_door_tile, KeyColor, KEY_COLORS};
+use crate::framebuffer::Framebuffer;
+use crate::input::{Action, Input};
+use crate::leaderboard::{format_ticks, hash_maze, Entry, Leaderboard, MAX_NAME_LEN};
+use crate::maze::{is_floor, tile_name, Floor};
+use crate::minimap::{render_minimap, zoom_label, MINIMAP_ZOOMS};
+use crate::profiler::{self, Phase};
+use crate::replay::{Replay, ReplayCheck};
+use crate::render::{
+    heart_shape, highlight_ray, key_shape, render2d, render3d, render_cat_sprite, render_image, Camera2d,
+    HIGHLIGHT_COLOR, INTRO_SCREEN, LOSE_SCREEN, VIEW2D_BLOCKS, VIEW2D_BLOCKS_MAX, VIEW2D_BLOCKS_MIN, WIN_SCREEN,
+};
+use crate::save::{read_slot, slot_label, slot_summary, write_slot, SaveError, SaveGame, SavedLevel, QUICK_SLOT, SAVE_SLOTS};
+use crate::settings::{Settings, FOV_MAX_DEGREES, FOV_MIN_DEGREES, SETTING_ITEMS};
+use crate::storage::{LoggedStorage, MemoryStorage, Storage};
+use crate::text::{Align, TextStyle};
+use crate::world::{
+    Cat, Damage, InteractOutcome, Level, LevelSource, MoveOutcome, World, HURT_FLASH_TIME, MAX_HEALTH, START_LIVES, WORLD_HEIGHT,
+};
+use nalgebra_glm::Vec2;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    TwoD,
+    ThreeD,
+}
+
+impl View {
+    pub fn toggled(self) -> Self {
+        match self {
+            View::TwoD => View::ThreeD,
+            View::ThreeD => View::TwoD,
+        }
+    }
+}
+
+// Estados del juego; cada uno tiene su propia entrada, actualización y render
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameState {
+    Intro,
+    Menu { selected: usize },
+    Playing { view: View },
+    Paused { view: View, selected: usize },
+    Options { view: View, selected: usize },
+    // Elegir ranura; sin vista cuando se entra desde el menú principal
+    SaveSlots { view: Option<View>, selected: usize, saving: bool },
+    LevelComplete { view: View },
+    Won,
+    Lost,
+}
+
+// Lo que pasó en el juego, para que `main` reaccione (audio, salir, ...)
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    StateChanged { from: GameState, to: GameState },
+    CarrotCollected { score: u32 },
+    Hurt { health: u32 },
+    LifeLost { lives: u32 },
+    Healed { health: u32 },
+    KeyCollected(KeyColor),
+    DoorOpened { pos: Vec2 },
+    DoorLocked(KeyColor),
+    CatMeow { pos: Vec2 }, // Solo para el audio
+    LevelCompleted { level: usize },
+    Won,
+    Lost,
+    Footstep { floor: Floor }, // Un paso del jugador, según lo que anduvo
+    SettingsChanged,
+    Saved { slot: usize },
+    Loaded { slot: usize },
+    SaveFailed { slot: usize, error: String },
+    NewRecord { rank: usize },
+    Quit,
+}
+
+const END_SCREEN_HINT: &str = "R: reintentar · N: laberinto nuevo · Enter: menú";
+
+const MENU_ITEMS: [&str; 4] = ["Jugar", "Cargar partida", "Laberinto nuevo", "Salir"];
+const MENU_LOAD: usize = 1; // Posición de "Cargar partida" en `MENU_ITEMS`
+
+const PAUSE_ITEMS: [&str; 7] = [
+    "Continuar",
+    "Reiniciar nivel",
+    "Guardar partida",
+    "Cargar partida",
+    "Opciones",
+    "Menú principal",
+    "Salir",
+];
+// Posiciones en `PAUSE_ITEMS` a las que se vuelve desde los submenús
+const PAUSE_SAVE: usize = 2;
+const PAUSE_LOAD: usize = 3;
+const PAUSE_OPTIONS: usize = 4;
+
+// La simulación avanza a paso fijo, igual con ventana que sin ella
+pub const TICK_RATE: u32 = 60;
+pub const TICK_DT: f32 = 1.0 / TICK_RATE as f32;
+
+// Segundos que se muestra un aviso ("Partida guardada", ...)
+const MESSAGE_TIME: f32 = 2.5;
+// Archivos de órdenes que se pueden ejecutar unos dentro de otros con `exec`
+const MAX_SCRIPT_DEPTH: usize = 8;
+
+// Partida cronometrada: empieza al salir de la intro o al elegir un laberinto
+#[derive(Clone, Debug)]
+pub struct Run {
+    pub ticks: u64,       // Ticks jugados, sin contar pausas ni menús
+    pub splits: Vec<u64>, // Tiempo total al terminar cada nivel
+    pub key: u64,         // Hash de los laberintos jugados, para la tabla de récords
+    pub ranked: bool,     // Las partidas cargadas no entran en la tabla
+}
+
+impl Run {
+    fn new(maze: &[Vec<char>]) -> Self {
+        Self { ticks: 0, splits: Vec::new(), key: hash_maze(None, maze), ranked: true }
+    }
+
+    // Tiempo del nivel en curso (o del último terminado)
+    pub fn level_ticks(&self) -> u64 {
+        let before = match self.splits.len() {
+            0 | 1 => 0,
+            n => self.splits[n - 2],
+        };
+        self.splits.last().copied().unwrap_or(self.ticks) - before
+    }
+}
+
+pub struct Game {
+    pub state: GameState,
+    pub levels: Vec<String>,
+    pub level: usize,
+    pub current: Level, // Estado inicial del nivel en juego, para reiniciarlo
+    pub world: World,
+    pub settings: Settings,
+    pub run: Run,
+    leaderboard: Option<Leaderboard>, // Tabla del laberinto, cargada al ganar
+    name_entry: Option<String>,       // Nombre que se está escribiendo para un récord
+    last_rank: Option<usize>,         // Puesto del último récord guardado
+    player_name: String,              // Último nombre usado, para proponerlo
+    start_score: u32,   // Puntos al empezar el nivel actual
+    start_lives: u32,   // Vidas al empezar el nivel actual
+    last_view: View,    // Vista con la que se jugó por última vez
+    pub ticks: u64,     // Ticks de simulación desde el principio
+    seed: u64,
+    rng: StdRng,        // Semillas de los laberintos generados
+    recording: Option<Replay>,
+    storage: Box<dyn Storage>, // Partidas guardadas y tablas de récords
+    slot_summaries: Vec<String>, // Lo que se muestra de cada ranura al elegirla
+    message: Option<(String, f32)>, // Aviso y segundos que le quedan
+    automap: Automap,
+    view2d_blocks: f32, // Zoom de la vista 2D: bloques que caben de arriba abajo
+    split_view: bool,   // Vista de depuración con el 3D y el 2D a la vez
+    pointer: Option<Vec2>, // Ratón sobre la pantalla, de 0 a 1 en cada eje
+    pinned_column: Option<f32>, // Columna fijada con un clic, de 0 a 1 como `pointer`
+    debug_overlay: bool, // Panel de desarrollo con tiempos y rayos
+    console: Console,
+    script_depth: usize, // Archivos de `exec` que se están ejecutando
+    events: Vec<GameEvent>,
+}
+
+impl Game {
+    pub fn new(levels: Vec<String>, seed: u64, settings: Settings) -> Self {
+        let current = Level::load(&levels[0], 0);
+        let mut world = World::new(&current);
+        world.player.fov = settings.fov();
+        let run = Run::new(&current.maze);
+        Self {
+            state: GameState::Intro,
+            levels,
+            level: 0,
+            current,
+            world,
+            settings,
+            run,
+            leaderboard: None,
+            name_entry: None,
+            last_rank: None,
+            player_name: String::new(),
+            start_score: 0,
+            start_lives: START_LIVES,
+            last_view: View::TwoD,
+            ticks: 0,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            recording: None,
+            storage: Box::new(MemoryStorage::default()),
+            slot_summaries: Vec::new(),
+            message: None,
+            automap: Automap::default(),
+            view2d_blocks: VIEW2D_BLOCKS,
+            split_view: false,
+            pointer: None,
+            pinned_column: None,
+            debug_overlay: false,
+            console: Console::default(),
+            script_depth: 0,
+            events: Vec::new(),
+        }
+    }
+
+    // Con la vista partida el ratón elige columna en vez de girar
+    pub fn split_view(&self) -> bool {
+        self.split_view
+    }
+
+    // Posición del ratón de 0 a 1 sobre la imagen, o `None` si está fuera
+    pub fn set_pointer(&mut self, pointer: Option<(f32, f32)>) {
+        self.pointer = pointer.map(|(x, y)| Vec2::new(x, y));
+    }
+
+    // Un clic en la mitad 3D fija la columna; en otro sitio la suelta
+    pub fn click(&mut self) {
+        self.pinned_column = self.pointer.map(|p| p.x).filter(|&x| x < 0.5);
+    }
+
+    // Columna que se inspecciona en la vista partida, de 0 a 1 sobre la pantalla
+    fn split_column(&self) -> Option<f32> {
+        self.pointer.map(|p| p.x).filter(|&x| x < 0.5).or(self.pinned_column)
+    }
+
+    // Dónde se leen y escriben las partidas y los récords. Por defecto en
+    // memoria; la ventana pone los archivos de verdad
+    pub fn set_storage(&mut self, storage: Box<dyn Storage>) {
+        self.storage = storage;
+    }
+
+    // El almacenamiento, apuntando en la grabación lo que se lee por primera vez
+    fn storage(&mut self) -> LoggedStorage<'_> {
+        let log = self.recording.as_mut().map(|replay| &mut replay.files);
+        LoggedStorage { inner: self.storage.as_mut(), log }
+    }
+
+    // Eventos acumulados desde la última llamada
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    // Vista del laberinto que se está mostrando, si hay alguna
+    pub fn view(&self) -> Option<View> {
+        match self.state {
+            GameState::Playing { view }
+            | GameState::Paused { view, .. }
+            | GameState::Options { view, .. }
+            | GameState::LevelComplete { view } => Some(view),
+            GameState::SaveSlots { view, .. } => view,
+            _ => None,
+        }
+    }
+
+    pub fn transition(&mut self, to: GameState) {
+        let from = self.state;
+        if let Some(view) = self.view() {
+            self.last_view = view;
+        }
+        self.state = to;
+        self.events.push(GameEvent::StateChanged { from, to });
+        self.on_enter(to, from);
+    }
+
+    fn on_enter(&mut self, to: GameState, _from: GameState) {
+        match to {
+            GameState::LevelComplete { .. } => {
+                self.run.splits.push(self.run.ticks);
+                self.events.push(GameEvent::LevelCompleted { level: self.level });
+            }
+            GameState::Won => {
+                self.run.splits.push(self.run.ticks);
+                self.enter_leaderboard();
+                self.events.push(GameEvent::Won);
+            }
+            GameState::Lost => self.events.push(GameEvent::Lost),
+            _ => {}
+        }
+    }
+
+    // Carga el nivel `level`; los puntos y las vidas se conservan entre niveles
+    fn start_level(&mut self, level: usize, score: u32, lives: u32) {
+        self.level = level;
+        self.play(Level::load(&self.levels[level], level), score, lives);
+    }
+
+    // Empieza a cronometrar una partida nueva desde el nivel actual
+    fn begin_run(&mut self) {
+        self.run = Run::new(&self.current.maze);
+    }
+
+    // Carga la tabla del laberinto y, si el tiempo entra, pide el nombre
+    fn enter_leaderboard(&mut self) {
+        let key = self.run.key;
+        let board = Leaderboard::load(&mut self.storage(), key);
+        let qualifies = self.run.ranked && board.rank_for(self.run.ticks, self.world.score).is_some();
+        self.name_entry = qualifies.then(|| self.player_name.clone());
+        self.last_rank = None;
+        self.leaderboard = Some(board);
+    }
+
+    // Mientras se escribe el nombre de un récord las teclas solo escriben
+    pub fn entering_name(&self) -> bool {
+        self.name_entry.is_some()
+    }
+
+    // Carácter del nombre. `Enter` llega también como tecla y es la tecla la
+    // que lo guarda: el orden entre las dos no está garantizado
+    fn name_input(&mut self, c: char) {
+        let Some(name) = &mut self.name_entry else { return };
+        match c {
+            '\u{8}' | '\u{7f}' => {
+                name.pop();
+            }
+            c if !c.is_control() && name.chars().count() < MAX_NAME_LEN => name.push(c),
+            _ => {}
+        }
+    }
+
+    fn submit_record(&mut self) {
+        let Some(name) = self.name_entry.take() else { return };
+        let name = match name.trim() {
+            "" => "Anónimo".to_string(),
+            name => name.to_string(),
+        };
+        let Some(board) = &mut self.leaderboard else { return };
+        let entry = Entry { name: name.clone(), ticks: self.run.ticks, carrots: self.world.score, splits: self.run.splits.clone() };
+        self.last_rank = board.insert(entry);
+        let saved = board.save(self.storage.as_mut());
+        self.player_name = name;
+        if let Err(err) = saved {
+            self.show_message(format!("No se pudo guardar el récord: {}", err));
+        }
+        if let Some(rank) = self.last_rank {
+            self.events.push(GameEvent::NewRecord { rank });
+        }
+    }
+
+    // Laberinto generado con una semilla nueva
+    fn start_generated(&mut self, score: u32, lives: u32) {
+        let seed = self.rng.gen();
+        self.play(Level::generate(seed), score, lives);
+    }
+
+    // Vuelve a empezar el nivel actual tal como era al principio
+    fn restart_level(&mut self) {
+        self.world = World::new(&self.current);
+        self.world.player.fov = self.settings.fov();
+        self.world.score = self.start_score;
+        self.world.lives = self.start_lives;
+    }
+
+    fn play(&mut self, level: Level, score: u32, lives: u32) {
+        self.current = level;
+        self.start_score = score;
+        self.start_lives = lives;
+        self.restart_level();
+    }
+
+    // Hay otro nivel de la lista después del actual
+    fn has_next_level(&self) -> bool {
+        match self.current.source {
+            LevelSource::File(i) => i + 1 < self.levels.len(),
+            LevelSource::Generated(_) => false,
+        }
+    }
+
+    // Partida en curso, si se está dentro de un nivel
+    pub fn snapshot(&self) -> Option<SaveGame> {
+        let view = self.view()?;
+        let level = match self.current.source {
+            LevelSource::File(_) => SavedLevel::File(self.current.name.clone()),
+            LevelSource::Generated(seed) => SavedLevel::Generated(seed),
+        };
+        let cells_with = |target: fn(char) -> bool| -> Vec<(usize, usize)> {
+            self.world
+                .maze
+                .iter()
+                .enumerate()
+                .flat_map(|(row, line)| {
+                    line.iter().enumerate().filter(move |(_, &c)| target(c)).map(move |(col, _)| (col, row))
+                })
+                .collect()
+        };
+        Some(SaveGame {
+            level,
+            view,
+            player_pos: self.world.player.pos,
+            player_a: self.world.player.a,
+            player_fov: self.world.player.fov,
+            score: self.world.score,
+            start_score: self.start_score,
+            elapsed: self.world.elapsed,
+            health: self.world.health,
+            lives: self.world.lives,
+            start_lives: self.start_lives,
+            invulnerable: self.world.invulnerable,
+            carrots: cells_with(|c| c == 'z'),
+            hearts: cells_with(|c| c == 'h'),
+            keys: cells_with(|c| KeyColor::from_key_tile(c).is_some()),
+            held_keys: self.world.keys.clone(),
+            doors: self.world.doors.iter().map(|d| (d.col, d.row, d.open, d.opening)).collect(),
+            cats: self.world.cats.iter().map(|cat| (cat.pos, cat.direction)).collect(),
+            explored: self.world.explored.clone(),
+            collected: self.world.collected.clone(),
+            trail: self.world.trail.clone(),
+        })
+    }
+
+    // Vuelve a poner la partida guardada y sigue jugando
+    pub fn restore(&mut self, save: &SaveGame) -> Result<(), SaveError> {
+        let level = match &save.level {
+            SavedLevel::File(path) => {
+                let index = self.levels.iter().position(|l| l == path).ok_or_else(|| {
+                    SaveError::LevelMismatch(format!("`{}` no está en la lista de niveles", path))
+                })?;
+                if !std::path::Path::new(path).exists() {
+                    return Err(SaveError::LevelMismatch(format!("no se encuentra `{}`", path)));
+                }
+                Level::load(path, index)
+            }
+            SavedLevel::Generated(seed) => Level::generate(*seed),
+        };
+
+        let mut world = World::new(&level);
+        // Solo quedan las zanahorias, corazones, llaves y puertas guardados, y
+        // tienen que estar en el laberinto
+        let door_cells: Vec<(usize, usize)> = save.doors.iter().map(|&(col, row, _, _)| (col, row)).collect();
+        type Pickup<'a> = (fn(char) -> bool, &'a str, &'a [(usize, usize)]);
+        let pickups: [Pickup; 4] = [
+            (|c| c == 'z', "zanahoria", &save.carrots),
+            (|c| c == 'h', "corazón", &save.hearts),
+            (|c| KeyColor::from_key_tile(c).is_some(), "llave", &save.keys),
+            (is_door_tile, "puerta", &door_cells),
+        ];
+        for (is_target, name, cells) in pickups {
+            for row in world.maze.iter_mut() {
+                for cell in row.iter_mut().filter(|c| is_target(**c)) {
+                    *cell = ' ';
+                }
+            }
+            for &(col, row) in cells {
+                match level.maze.get(row).and_then(|line| line.get(col)) {
+                    Some(&c) if is_target(c) => world.maze[row][col] = c,
+                    _ => return Err(SaveError::LevelMismatch(format!("no hay {} en ({}, {})", name, col, row))),
+                }
+            }
+        }
+        world.doors.retain(|d| is_door_tile(world.maze[d.row][d.col]));
+        for door in world.doors.iter_mut() {
+            if let Some(&(_, _, open, opening)) = save.doors.iter().find(|d| (d.0, d.1) == (door.col, door.row)) {
+                door.open = open;
+                door.opening = opening;
+            }
+        }
+        world.keys = save.held_keys.clone();
+        if save.health == 0 || save.health > MAX_HEALTH || save.lives == 0 {
+            return Err(SaveError::LevelMismatch("vida no válida".to_string()));
+        }
+
+        if save.cats.len() != world.cats.len() {
+            return Err(SaveError::LevelMismatch(format!(
+                "{} gatos guardados, el nivel tiene {}",
+                save.cats.len(),
+                world.cats.len()
+            )));
+        }
+        // Dentro de una celda que existe: el render y el sonido miran la celda
+        // de cada posición sin comprobarla
+        let bs = world.block_size as f32;
+        let maze = &world.maze;
+        let in_maze = |pos: Vec2| {
+            pos.x >= 0.0
+                && pos.y >= 0.0
+                && maze.get((pos.y / bs) as usize).is_some_and(|line| (pos.x / bs) < line.len() as f32)
+        };
+        if let Some(i) = save.cats.iter().position(|&(pos, _)| !in_maze(pos)) {
+            return Err(SaveError::LevelMismatch(format!("el gato {} está fuera del laberinto", i + 1)));
+        }
+        let pos = save.player_pos;
+        if !in_maze(pos) {
+            return Err(SaveError::LevelMismatch("el jugador está fuera del laberinto".to_string()));
+        }
+        let fov = save.player_fov.to_degrees();
+        if !save.player_a.is_finite() || !(FOV_MIN_DEGREES..=FOV_MAX_DEGREES).contains(&fov) {
+            return Err(SaveError::LevelMismatch("ángulo o campo de visión no válidos".to_string()));
+        }
+        for (cat, &(pos, direction)) in world.cats.iter_mut().zip(&save.cats) {
+            cat.pos = pos;
+            cat.direction = direction;
+        }
+        world.teleport(pos);
+        world.player.a = save.player_a;
+        world.player.fov = save.player_fov;
+        // Las partidas de antes del minimapa solo conocen lo que se ve desde aquí
+        if !save.explored.is_empty() {
+            let fits = save.explored.len() == world.maze.len()
+                && save.explored.iter().zip(&world.maze).all(|(seen, line)| seen.len() == line.len());
+            if !fits {
+                return Err(SaveError::LevelMismatch("el mapa explorado no tiene el tamaño del laberinto".to_string()));
+            }
+            world.explored = save.explored.clone();
+        }
+        world.reveal();
+        if !save.trail.is_empty() {
+            world.trail = save.trail.clone();
+            world.trail.push(vec![pos]);
+        }
+        world.collected = save.collected.clone();
+        world.score = save.score;
+        world.elapsed = save.elapsed;
+        world.health = save.health;
+        world.lives = save.lives;
+        world.invulnerable = save.invulnerable;
+
+        if let LevelSource::File(index) = level.source {
+            self.level = index;
+        }
+        self.current = level;
+        self.world = world;
+        self.start_score = save.start_score;
+        self.start_lives = save.start_lives;
+        self.run = Run::new(&self.current.maze);
+        self.run.ticks = (save.elapsed * TICK_RATE as f32) as u64;
+        self.run.ranked = false;
+        self.transition(GameState::Playing { view: save.view });
+        Ok(())
+    }
+
+    pub fn save_to_slot(&mut self, slot: usize) {
+        let Some(save) = self.snapshot() else { return };
+        match write_slot(&mut self.storage(), &save, slot) {
+            Ok(_) => {
+                self.show_message(format!("Partida guardada (ranura {})", slot_label(slot)));
+                self.events.push(GameEvent::Saved { slot });
+            }
+            Err(err) => self.save_failed(slot, err),
+        }
+    }
+
+    pub fn load_from_slot(&mut self, slot: usize) {
+        let save = read_slot(&mut self.storage(), slot);
+        match save.and_then(|save| self.restore(&save)) {
+            Ok(()) => {
+                self.show_message(format!("Partida cargada (ranura {})", slot_label(slot)));
+                self.events.push(GameEvent::Loaded { slot });
+            }
+            Err(err) => self.save_failed(slot, err),
+        }
+    }
+
+    fn save_failed(&mut self, slot: usize, err: SaveError) {
+        self.show_message(format!("Error: {}", err));
+        self.events.push(GameEvent::SaveFailed { slot, error: err.to_string() });
+    }
+
+    fn show_message(&mut self, text: String) {
+        self.message = Some((text, MESSAGE_TIME));
+    }
+
+    fn open_save_slots(&mut self, view: Option<View>, saving: bool) {
+        let mut storage = self.storage();
+        self.slot_summaries = (0..=SAVE_SLOTS).map(|slot| slot_summary(&mut storage, slot)).collect();
+        self.transition(GameState::SaveSlots { view, selected: 0, saving });
+    }
+
+    // Mientras la consola está abierta las teclas escriben en ella
+    pub fn console_open(&self) -> bool {
+        self.console.open
+    }
+
+    pub fn console(&self) -> &Console {
+        &self.console
+    }
+
+    fn console_input(&mut self, action: Action, pressed: bool) {
+        if !pressed {
+            return;
+        }
+        match action {
+            Action::Console => self.console.toggle(),
+            Action::HistoryPrevious => self.console.history_previous(),
+            Action::HistoryNext => self.console.history_next(),
+            _ => {}
+        }
+    }
+
+    fn console_text(&mut self, c: char) {
+        match c {
+            '\r' | '\n' => {
+                let line = self.console.submit();
+                self.run_command(&line);
+            }
+            c => self.console.type_char(c),
+        }
+    }
+
+    // Órdenes de arranque: cada línea se teclea en la consola como entradas
+    // normales, así que si se está grabando la repetición las vuelve a ejecutar
+    pub fn run_startup_script(&mut self, path: &str) -> std::io::Result<()> {
+        // Sin apuntarlo en la grabación: lo que se graba son las líneas
+        let text = self.storage.read(path)?;
+        let was_open = self.console.open;
+        if !was_open {
+            self.press(Action::Console);
+        }
+        for line in text.lines() {
+            // El tabulador autocompletaría
+            for c in line.chars().map(|c| if c == '\t' { ' ' } else { c }) {
+                self.apply(Input::Text(c));
+            }
+            self.apply(Input::Text('\n'));
+        }
+        if !was_open {
+            self.press(Action::Console);
+        }
+        Ok(())
+    }
+
+    fn press(&mut self, action: Action) {
+        self.apply(Input::Key { action, pressed: true });
+        self.apply(Input::Key { action, pressed: false });
+    }
+
+    // Ejecuta las órdenes de un archivo, una por línea, como si se escribieran
+    // en la consola. Se lee del almacenamiento para que una grabación lo guarde
+    pub fn exec_script(&mut self, path: &str) -> std::io::Result<()> {
+        let text = self.storage().read(path)?;
+        if self.script_depth >= MAX_SCRIPT_DEPTH {
+            self.console.print(format!("{}: demasiados exec anidados", path));
+            return Ok(());
+        }
+        self.script_depth += 1;
+        for line in text.lines() {
+            self.run_command(line);
+        }
+        self.script_depth -= 1;
+        Ok(())
+    }
+
+    // Una línea de la consola; lo que responde queda en su salida
+    pub fn run_command(&mut self, line: &str) {
+        let command = match parse_command(line) {
+            Ok(Some(command)) => command,
+            Ok(None) => return,
+            Err(err) => {
+                self.console.print(format!("> {}", line.trim()));
+                self.console.print(err.to_string());
+                return;
+            }
+        };
+        self.console.print(format!("> {}", line.trim()));
+        if command.is_cheat() {
+            self.run.ranked = false;
+        }
+        let reply = self.execute(command);
+        if !reply.is_empty() {
+            self.console.print(reply);
+        }
+    }
+
+    fn execute(&mut self, command: Command) -> String {
+        let bs = self.world.block_size as f32;
+        let yes_no = |on: bool| if on { "sí" } else { "no" };
+        match command {
+            Command::Help => {
+                for (name, args, description) in COMMANDS {
+                    let usage = if args.is_empty() { name.to_string() } else { format!("{} {}", name, args) };
+                    self.console.print(format!("{}: {}", usage, description));
+                }
+                String::new()
+            }
+            Command::Teleport { col, row } => match self.free_cell(col, row) {
+                Ok(center) => {
+                    self.world.teleport(center);
+                    self.world.reveal();
+                    format!("Jugador en la celda {}, {}", col, row)
+                }
+                Err(reason) => reason,
+            },
+            Command::Fov(degrees) => {
+                self.settings.set("fov", &degrees.to_string());
+                self.world.player.fov = self.settings.fov();
+                self.events.push(GameEvent::SettingsChanged);
+                format!("Campo de visión: {:.0}°", self.settings.fov_degrees)
+            }
+            Command::Noclip => {
+                self.world.noclip = !self.world.noclip;
+                format!("noclip: {}", yes_no(self.world.noclip))
+            }
+            Command::God => {
+                self.world.god = !self.world.god;
+                format!("god: {}", yes_no(self.world.god))
+            }
+            Command::Score(points) => {
+                self.world.score = self.world.score.saturating_add(points);
+                format!("Puntos: {}", self.world.score)
+            }
+            Command::Reload => {
+                match self.current.source {
+                    LevelSource::File(index) => self.start_level(index, self.start_score, self.start_lives),
+                    LevelSource::Generated(seed) => self.play(Level::generate(seed), self.start_score, self.start_lives),
+                }
+                format!("Nivel recargado: {}", self.current.name)
+            }
+            Command::Spawn(cell) => {
+                // Sin celda, dos bloques por delante del jugador
+                let (col, row) = cell.unwrap_or_else(|| {
+                    let player = &self.world.player;
+                    let ahead = player.pos + Vec2::new(player.a.cos(), player.a.sin()) * bs * 2.0;
+                    ((ahead.x / bs).max(0.0) as usize, (ahead.y / bs).max(0.0) as usize)
+                });
+                match self.free_cell(col, row) {
+                    Ok(center) => {
+                        self.world.cats.push(Cat::new(center, center + Vec2::new(0.0, bs * 0.35), 0.05));
+                        format!("Gato en la celda {}, {}", col, row)
+                    }
+                    Err(reason) => reason,
+                }
+            }
+            Command::Fog => {
+                self.settings.fog = !self.settings.fog;
+                self.events.push(GameEvent::SettingsChanged);
+                format!("Niebla: {}", yes_no(self.settings.fog))
+            }
+            Command::Minimap => {
+                self.settings.minimap = self.settings.minimap.next();
+                self.events.push(GameEvent::SettingsChanged);
+                format!("Minimapa: {}", self.settings.minimap.label())
+            }
+            Command::Set { key, value } => {
+                let ok = match key.as_str() {
+                    "health" => value.parse().map(|v: u32| self.world.health = v.clamp(1, MAX_HEALTH)).is_ok(),
+                    "lives" => value.parse().map(|v: u32| self.world.lives = v.max(1)).is_ok(),
+                    _ => {
+                        let ok = self.settings.set(&key, &value);
+                        if ok {
+                            self.world.player.fov = self.settings.fov();
+                            self.events.push(GameEvent::SettingsChanged);
+                        }
+                        ok
+                    }
+                };
+                if ok {
+                    format!("{} = {}", key, value)
+                } else {
+                    format!("No se puede poner {} a {}", key, value)
+                }
+            }
+            Command::Exec(path) => match self.exec_script(&path) {
+                Ok(()) => String::new(),
+                Err(err) => format!("No se pudo leer {}: {}", path, err),
+            },
+            Command::Clear => {
+                self.console.output.clear();
+                String::new()
+            }
+        }
+    }
+
+    // Centro de la celda si se puede estar en ella (con noclip, cualquiera
+    // dentro del laberinto)
+    fn free_cell(&self, col: usize, row: usize) -> Result<Vec2, String> {
+        let bs = self.world.block_size as f32;
+        let Some(&tile) = self.world.maze.get(row).and_then(|line| line.get(col)) else {
+            return Err(format!("La celda {}, {} está fuera del laberinto", col, row));
+        };
+        let center = Vec2::new((col as f32 + 0.5) * bs, (row as f32 + 0.5) * bs);
+        if is_floor(tile) || (self.world.noclip && self.world.inside(center)) {
+            Ok(center)
+        } else {
+            Err(format!("La celda {}, {} no está libre: {}", col, row, tile_name(tile)))
+        }
+    }
+
+    // Empieza a grabar las entradas. La repetición arranca de un juego nuevo,
+    // así que hay que llamarla antes del primer tick
+    pub fn start_recording(&mut self) {
+        debug_assert_eq!(self.ticks, 0, "la grabación tiene que empezar con el juego");
+        self.recording = Some(Replay::new(self.seed, self.levels.clone(), self.settings.fov_degrees));
+    }
+
+    // Termina la grabación y la devuelve con el estado final para comprobarla
+    pub fn finish_recording(&mut self) -> Option<Replay> {
+        let mut replay = self.recording.take()?;
+        replay.ticks = self.ticks;
+        replay.check = Some(ReplayCheck::of(self));
+        Some(replay)
+    }
+
+    // Punto de entrada de todo lo que hace el jugador
+    pub fn apply(&mut self, input: Input) {
+        if let Some(replay) = &mut self.recording {
+            replay.inputs.push((self.ticks, input));
+        }
+        // Con la consola abierta el juego no recibe nada
+        match input {
+            Input::Text(_) if self.console.skip_toggle_char() => {}
+            Input::Key { action: Action::Console, pressed: false } => self.console.toggle_released(),
+            Input::Key { action, pressed } if self.console.open => self.console_input(action, pressed),
+            Input::Key { action: Action::Console, pressed: true } => self.console.toggle(),
+            Input::Key { action, pressed } => self.handle_action(action, pressed),
+            Input::Look(_) if self.console.open => {}
+            Input::Look(delta) => self.look(delta),
+            Input::Text(c) if self.console.open => self.console_text(c),
+            Input::Text(c) => self.name_input(c),
+        }
+    }
+
+    // Avanza la simulación un paso de `TICK_DT`
+    pub fn tick(&mut self) {
+        if let (GameState::Playing { .. }, false) = (self.state, self.console.open) {
+            self.run.ticks += 1;
+        }
+        self.update(TICK_DT);
+        self.ticks += 1;
+    }
+
+    fn handle_action(&mut self, action: Action, pressed: bool) {
+        if pressed {
+            match (action, self.state) {
+                (Action::QuickSave, GameState::Playing { .. } | GameState::Paused { .. }) => {
+                    self.save_to_slot(QUICK_SLOT);
+                    return;
+                }
+                (Action::QuickLoad, GameState::Playing { .. } | GameState::Paused { .. } | GameState::Menu { .. })
+                | (Action::QuickLoad, GameState::Won | GameState::Lost) => {
+                    self.load_from_slot(QUICK_SLOT);
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        match self.state {
+            GameState::Intro => self.intro_input(pressed),
+            GameState::Menu { selected } => self.menu_input(selected, action, pressed),
+            GameState::Playing { view } => self.playing_input(view, action, pressed),
+            GameState::Paused { view, selected } => self.paused_input(view, selected, action, pressed),
+            GameState::Options { view, selected } => self.options_input(view, selected, action, pressed),
+            GameState::SaveSlots { view, selected, saving } => self.save_slots_input(view, selected, saving, action, pressed),
+            GameState::LevelComplete { view } => self.level_complete_input(view, action, pressed),
+            GameState::Won | GameState::Lost => self.end_screen_input(action, pressed),
+        }
+    }
+
+    // Giro con el ratón
+    fn look(&mut self, delta: f32) {
+        if let GameState::Playing { .. } = self.state {
+            self.world.player.a += delta;
+        }
+    }
+
+    // `dt`: segundos desde la última actualización
+    fn update(&mut self, dt: f32) {
+        if let Some((_, remaining)) = &mut self.message {
+            *remaining -= dt;
+            if *remaining <= 0.0 {
+                self.message = None;
+            }
+        }
+
+        if let (GameState::Playing { .. }, false) = (self.state, self.console.open) {
+            self.world.update(dt);
+            if let Some(floor) = self.world.footstep(dt) {
+                self.events.push(GameEvent::Footstep { floor });
+            }
+            for pos in self.world.meowing_cats(dt) {
+                self.events.push(GameEvent::CatMeow { pos });
+            }
+            self.check_cat_contact();
+        }
+    }
+
+    // Solo se pierde la partida al quedarse sin vidas
+    fn check_cat_contact(&mut self) {
+        match self.world.check_cat_contact() {
+            Some(Damage::Hurt) => self.events.push(GameEvent::Hurt { health: self.world.health }),
+            Some(Damage::LifeLost) => self.events.push(GameEvent::LifeLost { lives: self.world.lives }),
+            Some(Damage::Dead) => self.transition(GameState::Lost),
+            None => {}
+        }
+    }
+
+    fn intro_input(&mut self, pressed: bool) {
+        if pressed {
+            self.begin_run();
+            self.transition(GameState::Playing { view: View::TwoD });
+        }
+    }
+
+    fn menu_input(&mut self, selected: usize, action: Action, pressed: bool) {
+        if !pressed {
+            return;
+        }
+        match action {
+            Action::Forward => {
+                let selected = (selected + MENU_ITEMS.len() - 1) % MENU_ITEMS.len();
+                self.state = GameState::Menu { selected };
+            }
+            Action::Backward => {
+                let selected = (selected + 1) % MENU_ITEMS.len();
+                self.state = GameState::Menu { selected };
+            }
+            Action::Confirm => match selected {
+                0 => {
+                    self.start_level(0, 0, START_LIVES);
+                    self.begin_run();
+                    self.transition(GameState::Playing { view: View::TwoD });
+                }
+                MENU_LOAD => self.open_save_slots(None, false),
+                2 => {
+                    self.start_generated(0, START_LIVES);
+                    self.begin_run();
+                    self.transition(GameState::Playing { view: View::TwoD });
+                }
+                _ => self.events.push(GameEvent::Quit),
+            },
+            _ => {}
+        }
+    }
+
+    fn playing_input(&mut self, view: View, action: Action, pressed: bool) {
+        if !pressed {
+            return;
+        }
+
+        let outcome = match action {
+            Action::Forward => Some(self.world.move_forward()),
+            Action::Backward => Some(self.world.move_backward()),
+            Action::TurnLeft => {
+                self.world.turn_left();
+                None
+            }
+            Action::TurnRight => {
+                self.world.turn_right();
+                None
+            }
+            Action::Interact => {
+                match self.world.interact() {
+                    InteractOutcome::DoorOpened { pos } => self.events.push(GameEvent::DoorOpened { pos }),
+                    InteractOutcome::Locked(color) => {
+                        self.show_message(format!("Hace falta la llave {}", color.name()));
+                        self.events.push(GameEvent::DoorLocked(color));
+                    }
+                    InteractOutcome::Nothing => {}
+                }
+                return;
+            }
+            Action::ToggleView => {
+                self.transition(GameState::Playing { view: view.toggled() });
+                return;
+            }
+            Action::Pause => {
+                self.transition(GameState::Paused { view, selected: 0 });
+                return;
+            }
+            Action::SplitView => {
+                self.split_view = !self.split_view;
+                return;
+            }
+            Action::DebugOverlay => {
+                self.debug_overlay = !self.debug_overlay;
+                return;
+            }
+            Action::Automap => {
+                self.automap.toggle();
+                return;
+            }
+            Action::MapPanUp | Action::MapPanDown | Action::MapPanLeft | Action::MapPanRight
+            | Action::MapZoomIn | Action::MapZoomOut | Action::MapCenter => {
+                if self.automap.open {
+                    self.automap_input(action);
+                } else if view == View::TwoD || self.split_view {
+                    self.view2d_zoom(action);
+                }
+                return;
+            }
+            Action::MinimapZoom => {
+                self.settings.minimap_zoom = (self.settings.minimap_zoom + 1) % MINIMAP_ZOOMS.len();
+                self.show_message(format!("Minimapa: {}", zoom_label(self.settings.minimap_zoom)));
+                self.events.push(GameEvent::SettingsChanged);
+                return;
+            }
+            _ => None,
+        };
+
+        match outcome {
+            Some(MoveOutcome::CarrotCollected) => {
+                self.events.push(GameEvent::CarrotCollected { score: self.world.score });
+            }
+            Some(MoveOutcome::HealthPickup) => {
+                self.events.push(GameEvent::Healed { health: self.world.health });
+            }
+            Some(MoveOutcome::KeyCollected(color)) => {
+                self.show_message(format!("Llave {}", color.name()));
+                self.events.push(GameEvent::KeyCollected(color));
+            }
+            Some(MoveOutcome::ReachedGoal) => {
+                if self.has_next_level() {
+                    self.transition(GameState::LevelComplete { view });
+                } else {
+                    self.transition(GameState::Won);
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        self.check_cat_contact();
+    }
+
+    // Ranura rápida, las normales y una última entrada "Volver"
+    fn save_slots_input(&mut self, view: Option<View>, selected: usize, saving: bool, action: Action, pressed: bool) {
+        if !pressed {
+            return;
+        }
+        let count = SAVE_SLOTS + 2;
+        let back = match view {
+            Some(view) => GameState::Paused { view, selected: if saving { PAUSE_SAVE } else { PAUSE_LOAD } },
+            None => GameState::Menu { selected: MENU_LOAD },
+        };
+        match action {
+            Action::Forward => {
+                self.state = GameState::SaveSlots { view, selected: (selected + count - 1) % count, saving };
+            }
+            Action::Backward => {
+                self.state = GameState::SaveSlots { view, selected: (selected + 1) % count, saving };
+            }
+            Action::Confirm if selected > SAVE_SLOTS => self.transition(back),
+            Action::Confirm if saving => {
+                self.save_to_slot(selected);
+                self.transition(back);
+            }
+            Action::Confirm => self.load_from_slot(selected),
+            Action::Pause => self.transition(back),
+            _ => {}
+        }
+    }
+
+    fn paused_input(&mut self, view: View, selected: usize, action: Action, pressed: bool) {
+        if !pressed {
+            return;
+        }
+        match action {
+            Action::Forward => {
+                let selected = (selected + PAUSE_ITEMS.len() - 1) % PAUSE_ITEMS.len();
+                self.state = GameState::Paused { view, selected };
+            }
+            Action::Backward => {
+                let selected = (selected + 1) % PAUSE_ITEMS.len();
+                self.state = GameState::Paused { view, selected };
+            }
+            Action::Pause => self.transition(GameState::Playing { view }),
+            Action::Confirm => match selected {
+                0 => self.transition(GameState::Playing { view }),
+                1 => {
+                    self.restart_level();
+                    self.transition(GameState::Playing { view });
+                }
+                PAUSE_SAVE => self.open_save_slots(Some(view), true),
+                PAUSE_LOAD => self.open_save_slots(Some(view), false),
+                PAUSE_OPTIONS => self.transition(GameState::Options { view, selected: 0 }),
+                5 => self.transition(GameState::Menu { selected: 0 }),
+                _ => self.events.push(GameEvent::Quit),
+            },
+            _ => {}
+        }
+    }
+
+    // Las opciones más una última entrada "Volver"
+    fn options_input(&mut self, view: View, selected: usize, action: Action, pressed: bool) {
+        if !pressed {
+            return;
+        }
+        let count = SETTING_ITEMS.len() + 1;
+        let back = GameState::Paused { view, selected: PAUSE_OPTIONS };
+        match action {
+            Action::Forward => self.state = GameState::Options { view, selected: (selected + count - 1) % count },
+            Action::Backward => self.state = GameState::Options { view, selected: (selected + 1) % count },
+            Action::TurnLeft | Action::TurnRight | Action::Confirm => {
+                let Some(&item) = SETTING_ITEMS.get(selected) else {
+                    if action == Action::Confirm {
+                        self.transition(back);
+                    }
+                    return;
+                };
+                let step = if action == Action::TurnLeft { -1 } else { 1 };
+                self.settings.adjust(item, step);
+                self.world.player.fov = self.settings.fov();
+                self.events.push(GameEvent::SettingsChanged);
+            }
+            Action::Pause => self.transition(back),
+            _ => {}
+        }
+    }
+
+    fn automap_input(&mut self, action: Action) {
+        match action {
+            Action::MapPanUp => self.automap.pan_by(0.0, -1.0),
+            Action::MapPanDown => self.automap.pan_by(0.0, 1.0),
+            Action::MapPanLeft => self.automap.pan_by(-1.0, 0.0),
+            Action::MapPanRight => self.automap.pan_by(1.0, 0.0),
+            Action::MapZoomIn => self.automap.zoom_in(),
+            Action::MapZoomOut => self.automap.zoom_out(),
+            Action::MapCenter => self.automap.center(),
+            _ => {}
+        }
+    }
+
+    // `+` y `-` en la vista 2D cuando el automapa está cerrado
+    fn view2d_zoom(&mut self, action: Action) {
+        let blocks = match action {
+            Action::MapZoomIn => self.view2d_blocks / 1.25,
+            Action::MapZoomOut => self.view2d_blocks * 1.25,
+            _ => return,
+        };
+        self.view2d_blocks = blocks.clamp(VIEW2D_BLOCKS_MIN, VIEW2D_BLOCKS_MAX);
+    }
+
+    fn level_complete_input(&mut self, view: View, action: Action, pressed: bool) {
+        if pressed && action == Action::Confirm {
+            self.start_level(self.level + 1, self.world.score, self.world.lives);
+            self.run.key = hash_maze(Some(self.run.key), &self.current.maze);
+            self.transition(GameState::Playing { view });
+        }
+    }
+
+    fn end_screen_input(&mut self, action: Action, pressed: bool) {
+        if !pressed {
+            return;
+        }
+        // Mientras se escribe el nombre las teclas son letras; Enter lo guarda
+        // y Esc lo salta
+        if self.name_entry.is_some() {
+            match action {
+                Action::Confirm => self.submit_record(),
+                Action::Pause => self.name_entry = None,
+                _ => {}
+            }
+            return;
+        }
+        match action {
+            Action::Restart => {
+                self.restart_level();
+                self.begin_run();
+                self.transition(GameState::Playing { view: self.last_view });
+            }
+            Action::NewMaze => {
+                self.start_generated(0, START_LIVES);
+                self.begin_run();
+                self.transition(GameState::Playing { view: self.last_view });
+            }
+            Action::Confirm | Action::Pause => self.transition(GameState::Menu { selected: 0 }),
+            _ => {}
+        }
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer, fps: u32) {
+        match self.state {
+            GameState::Intro => render_image(framebuffer, &INTRO_SCREEN),
+            GameState::Menu { selected } => self.render_menu(framebuffer, selected),
+            GameState::Playing { view } => self.render_playing(framebuffer, view, fps),
+            GameState::Paused { view, selected } => {
+                self.render_playing(framebuffer, view, fps);
+                let items: Vec<String> = PAUSE_ITEMS.iter().map(|item| item.to_string()).collect();
+                render_panel(framebuffer, "Pausa", &items, selected, "Esc: continuar");
+            }
+            GameState::Options { view, selected } => {
+                self.render_playing(framebuffer, view, fps);
+                let mut items: Vec<String> = SETTING_ITEMS
+                    .iter()
+                    .map(|&item| format!("{}: {}", item.label(), self.settings.value_label(item)))
+                    .collect();
+                items.push("Volver".to_string());
+                render_panel(framebuffer, "Opciones", &items, selected, "A / D: cambiar · Esc: volver");
+            }
+            GameState::SaveSlots { view, selected, saving } => {
+                match view {
+                    Some(view) => self.render_playing(framebuffer, view, fps),
+                    None => render_image(framebuffer, &INTRO_SCREEN),
+                }
+                let mut items: Vec<String> = self
+                    .slot_summaries
+                    .iter()
+                    .enumerate()
+                    .map(|(slot, summary)| format!("Ranura {}: {}", slot_label(slot), summary))
+                    .collect();
+                items.push("Volver".to_string());
+                let title = if saving { "Guardar partida" } else { "Cargar partida" };
+                render_panel(framebuffer, title, &items, selected, "Enter: elegir · Esc: volver");
+            }
+            GameState::LevelComplete { view } => {
+                self.render_playing(framebuffer, view, fps);
+                let subtitle = format!(
+                    "Nivel: {} · Total: {} · Enter para continuar",
+                    format_ticks(self.run.level_ticks()),
+                    format_ticks(self.run.ticks)
+                );
+                render_banner(framebuffer, "¡Nivel completado!", &subtitle);
+            }
+            GameState::Won => {
+                // Show the "Fin.png" screen when the player wins
+                render_image(framebuffer, &WIN_SCREEN);
+                self.render_leaderboard(framebuffer);
+                match self.name_entry {
+                    Some(_) => render_hint(framebuffer, "Enter: guardar · Esc: no guardar"),
+                    None => render_hint(framebuffer, END_SCREEN_HINT),
+                }
+            }
+            GameState::Lost => {
+                // Mostrar la pantalla de que el jugador perdió
+                render_image(framebuffer, &LOSE_SCREEN);
+                render_hint(framebuffer, END_SCREEN_HINT);
+            }
+        }
+
+        if let Some((text, _)) = &self.message {
+            render_message(framebuffer, text);
+        }
+        render_console(framebuffer, &self.console, ui_scale(framebuffer));
+    }
+
+    // Paredes y gatos de la vista 3D
+    fn render_scene3d(&self, framebuffer: &mut Framebuffer) {
+        let world = &self.world;
+        render3d(framebuffer, &world.player, world.block_size, &world.maze, &world.doors, self.settings.fog);
+        let _timer = profiler::time(Phase::Sprites);
+        let distance_to_projection_plane = framebuffer.get_width() as f32 / 2.0 / (world.player.fov / 2.0).tan();
+        for cat in &world.cats {
+            render_cat_sprite(
+                framebuffer,
+                &world.player,
+                cat.pos,
+                distance_to_projection_plane,
+                world.block_size,
+                &world.maze,
+                300.0,
+            );
+        }
+    }
+
+    // Vista de depuración: el 3D a la izquierda y el 2D con los rayos a la
+    // derecha. La columna bajo el ratón, o la fijada con un clic, resalta su
+    // rayo en el 2D y enseña lo que devolvió `trace_ray`
+    fn render_split(&self, framebuffer: &mut Framebuffer) {
+        let world = &self.world;
+        let (width, height) = (framebuffer.get_width(), framebuffer.get_height());
+        let (left_w, right_w) = (width / 2, width - width / 2);
+        let column = self.split_column().map(|u| ((u * width as f32) as usize).min(left_w.saturating_sub(1)));
+        let angle = column.map(|c| world.player.a - world.player.fov / 2.0 + world.player.fov * c as f32 / left_w as f32);
+
+        let mut pane = vec![0u8; left_w * height * 4];
+        self.render_scene3d(&mut Framebuffer::new(left_w, height, &mut pane));
+        framebuffer.blit(&pane, left_w, height, 0, 0);
+
+        let mut pane = vec![0u8; right_w * height * 4];
+        let mut right = Framebuffer::new(right_w, height, &mut pane);
+        let camera =
+            Camera2d::follow(world.player.pos, &world.maze, world.block_size, self.view2d_blocks, right_w, height);
+        render2d(&mut right, &world.player, &world.maze, &world.doors, &camera, world.block_size);
+        let intersect = angle.map(|a| {
+            highlight_ray(&mut right, &camera, &world.player, &world.maze, &world.doors, a, world.block_size)
+        });
+        framebuffer.blit(&pane, right_w, height, left_w, 0);
+        framebuffer.vline(left_w as isize, 0, height as isize - 1, [0xFF, 0xFF, 0xFF, 0xFF]);
+
+        let (Some(column), Some(a), Some(intersect)) = (column, angle, intersect) else { return };
+        framebuffer.vline(column as isize, 0, height as isize - 1, HIGHLIGHT_COLOR);
+        let lines = [
+            format!("Columna {} · ángulo {:.1}°", column, a.to_degrees().rem_euclid(360.0)),
+            format!(
+                "Distancia: {:.1} ({:.2} bloques)",
+                intersect.distance,
+                intersect.distance / world.block_size as f32
+            ),
+            // Con el código del carácter: la fuente no tiene todos los símbolos
+            format!(
+                "Impacto: {} (0x{:02X}) · tx: {} · cara: {}",
+                tile_name(intersect.impact),
+                intersect.impact as u32,
+                intersect.tx,
+                intersect.face.name()
+            ),
+        ];
+        let ui_scale = ui_scale(framebuffer);
+        let line_height = (30.0 * ui_scale) as isize;
+        let margin = (10.0 * ui_scale) as isize;
+        let top = height as isize - line_height * lines.len() as isize - margin * 2;
+        framebuffer.blend_rect(0, top, left_w, height - top as usize, [0x00, 0x00, 0x00, 0xFF], 0.6);
+        let style = TextStyle::new(24.0 * ui_scale);
+        for (i, line) in lines.iter().enumerate() {
+            framebuffer.draw_text_styled(line, margin, top + margin + line_height * i as isize, &style);
+        }
+    }
+
+    fn render_playing(&self, framebuffer: &mut Framebuffer, view: View, fps: u32) {
+        let world = &self.world;
+        let (fb_width, fb_height) = (framebuffer.get_width(), framebuffer.get_height());
+
+        match view {
+            _ if self.split_view => self.render_split(framebuffer),
+            View::TwoD => {
+                let camera = Camera2d::follow(
+                    world.player.pos,
+                    &world.maze,
+                    world.block_size,
+                    self.view2d_blocks,
+                    fb_width,
+                    fb_height,
+                );
+                render2d(framebuffer, &world.player, &world.maze, &world.doors, &camera, world.block_size);
+            }
+            View::ThreeD => {
+                self.render_scene3d(framebuffer);
+                if !self.automap.open {
+                    let _timer = profiler::time(Phase::Minimap);
+                    let size = fb_height * 2 / 9;
+                    render_minimap(framebuffer, world, self.settings.minimap, self.settings.minimap_zoom, size);
+                }
+            }
+        }
+        if self.automap.open {
+            let _timer = profiler::time(Phase::Minimap);
+            render_automap(framebuffer, world, &self.automap);
+            render_hint(framebuffer, AUTOMAP_HINT);
+        }
+
+        let _timer = profiler::time(Phase::Hud);
+        let ui_scale = ui_scale(framebuffer);
+        let margin = (10.0 * ui_scale) as isize;
+        let shadow = (2.0 * ui_scale).max(1.0) as isize;
+        let hud_style = TextStyle::new(40.0 * ui_scale).shadow([0x00, 0x00, 0x00, 0xFF], shadow);
+        framebuffer.draw_text_styled(&format!("FPS: {}", fps), margin, margin, &hud_style);
+        let score_style = TextStyle::new(30.0 * ui_scale).align(Align::Right).shadow([0x00, 0x00, 0x00, 0xFF], shadow);
+        framebuffer.draw_text_styled(&format!("Puntos: {}", world.score), fb_width as isize - margin, margin, &score_style);
+        let timer_style = TextStyle::new(30.0 * ui_scale).align(Align::Center).shadow([0x00, 0x00, 0x00, 0xFF], shadow);
+        framebuffer.draw_text_styled(&format_ticks(self.run.ticks), fb_width as isize / 2, margin, &timer_style);
+
+        // Corazones debajo de los FPS; parpadean mientras dura la invulnerabilidad
+        let heart_size = (32.0 * ui_scale) as usize;
+        let hearts_y = margin + (50.0 * ui_scale) as isize;
+        let blink = world.invulnerable > 0.0 && ((world.invulnerable * 8.0) as u32).is_multiple_of(2);
+        for i in 0..MAX_HEALTH {
+            let x = margin + i as isize * (heart_size as isize + margin / 2);
+            let color = if i < world.health && !blink { [0xE0, 0x20, 0x40, 0xFF] } else { [0x40, 0x40, 0x40, 0xFF] };
+            draw_icon(framebuffer, x, hearts_y, heart_size, color, heart_shape);
+        }
+        let lives_x = margin + MAX_HEALTH as isize * (heart_size as isize + margin / 2) + margin;
+        let lives_style = TextStyle::new(30.0 * ui_scale).shadow([0x00, 0x00, 0x00, 0xFF], shadow);
+        framebuffer.draw_text_styled(&format!("x{}", world.lives), lives_x, hearts_y, &lives_style);
+
+        // Llaves recogidas, en el orden de los colores
+        let keys_y = hearts_y + heart_size as isize + margin / 2;
+        let held = KEY_COLORS.iter().filter(|c| world.keys.contains(c));
+        for (i, color) in held.enumerate() {
+            let x = margin + i as isize * (heart_size as isize + margin / 2);
+            draw_icon(framebuffer, x, keys_y, heart_size, color.rgba(), key_shape);
+        }
+
+        if world.hurt_flash > 0.0 {
+            let alpha = world.hurt_flash / HURT_FLASH_TIME * 0.45;
+            framebuffer.blend_rect(0, 0, fb_width, fb_height, [0xFF, 0x00, 0x00, 0xFF], alpha);
+        }
+
+        if self.debug_overlay {
+            render_debug_overlay(framebuffer, world, &profiler::last_frame(), ui_scale);
+        }
+    }
+
+    // Tiempo de la partida, parciales y la tabla de récords del laberinto
+    fn render_leaderboard(&self, framebuffer: &mut Framebuffer) {
+        let (w, h) = (framebuffer.get_width(), framebuffer.get_height());
+        let ui_scale = ui_scale(framebuffer);
+        let line = (34.0 * ui_scale) as isize;
+        let panel_w = (w as f32 * 0.5) as usize;
+        let panel_h = (line * 16) as usize;
+        let left = (w - panel_w) as isize / 2;
+        // Debajo del título de la imagen de victoria
+        let top = (h / 4) as isize;
+        framebuffer.blend_rect(left, top, panel_w, panel_h, [0x10, 0x10, 0x20, 0xFF], 0.75);
+
+        let white = [0xFF, 0xFF, 0xFF, 0xFF];
+        let gold = [0xFF, 0xD7, 0x00, 0xFF];
+        let center = w as isize / 2;
+        let mut y = top + line / 3;
+        let title = TextStyle::new(40.0 * ui_scale).align(Align::Center);
+        framebuffer.draw_text_styled(&format!("Tiempo: {}", format_ticks(self.run.ticks)), center, y, &title);
+        y += line * 3 / 2;
+
+        let small = TextStyle::new(22.0 * ui_scale).align(Align::Center).color([0xDD, 0xDD, 0xDD, 0xFF]);
+        if self.run.splits.len() > 1 {
+            let splits: Vec<String> = self.run.splits.iter().map(|&t| format_ticks(t)).collect();
+            framebuffer.draw_text_styled(&format!("Parciales: {}", splits.join(" · ")), center, y, &small);
+            y += line;
+        }
+        if !self.run.ranked {
+            framebuffer.draw_text_styled("Partida cargada: no entra en la tabla", center, y, &small);
+        }
+        y += line;
+
+        if let Some(name) = &self.name_entry {
+            let style = TextStyle::new(30.0 * ui_scale).align(Align::Center).color(gold);
+            framebuffer.draw_text_styled(&format!("¡Nuevo récord! Nombre: {}_", name), center, y, &style);
+        }
+        y += line * 3 / 2;
+
+        let col_name = left + (20.0 * ui_scale) as isize;
+        let col_time = left + panel_w as isize * 7 / 10;
+        let col_carrots = left + panel_w as isize - (20.0 * ui_scale) as isize;
+        let entries = self.leaderboard.as_ref().map(|b| b.entries.as_slice()).unwrap_or_default();
+        if entries.is_empty() {
+            framebuffer.draw_text_styled("Todavía no hay récords", center, y, &small);
+        }
+        for (i, entry) in entries.iter().enumerate() {
+            let color = if Some(i) == self.last_rank { gold } else { white };
+            let style = TextStyle::new(26.0 * ui_scale).color(color);
+            framebuffer.draw_text_styled(&format!("{}. {}", i + 1, entry.name), col_name, y, &style);
+            framebuffer.draw_text_styled(&format_ticks(entry.ticks), col_time, y, &style.align(Align::Right));
+            framebuffer.draw_text_styled(&format!("{} zanahorias", entry.carrots), col_carrots, y, &style.align(Align::Right));
+            y += line;
+        }
+    }
+
+    fn render_menu(&self, framebuffer: &mut Framebuffer, selected: usize) {
+        render_image(framebuffer, &INTRO_SCREEN);
+        let (w, h) = (framebuffer.get_width(), framebuffer.get_height());
+        framebuffer.blend_rect(0, 0, w, h, [0x00, 0x00, 0x00, 0xFF], 0.5);
+
+        let ui_scale = ui_scale(framebuffer);
+        let center = w as isize / 2;
+        let mut y = (h as f32 * 0.35) as isize;
+        let item_height = (60.0 * ui_scale) as isize;
+        for (i, item) in MENU_ITEMS.iter().enumerate() {
+            let color = if i == selected { [0xFF, 0xD7, 0x00, 0xFF] } else { [0xFF, 0xFF, 0xFF, 0xFF] };
+            let style = TextStyle::new(48.0 * ui_scale).color(color).align(Align::Center).shadow([0x00, 0x00, 0x00, 0xFF], 2);
+            let label = if i == selected { format!("> {} <", item) } else { item.to_string() };
+            framebuffer.draw_text_styled(&label, center, y, &style);
+            y += item_height;
+        }
+    }
+}
+
+// Icono de `size` píxeles con la esquina superior izquierda en (x, y); `shape`
+// dice qué puntos pintar, con coordenadas de 0 a 1
+fn draw_icon(framebuffer: &mut Framebuffer, x: isize, y: isize, size: usize, color: [u8; 4], shape: fn(f32, f32) -> bool) {
+    for dy in 0..size {
+        for dx in 0..size {
+            if shape(dx as f32 / size as f32, dy as f32 / size as f32) {
+                framebuffer.set_pixel(x + dx as isize, y + dy as isize, color);
+            }
+        }
+    }
+}
+
+// Tamaño del texto relativo a 900 px de alto
+fn ui_scale(framebuffer: &Framebuffer) -> f32 {
+    framebuffer.get_height() as f32 / WORLD_HEIGHT as f32
+}
+
+// Franja oscura con un título y una línea de ayuda en el centro de la pantalla
+fn render_banner(framebuffer: &mut Framebuffer, title: &str, subtitle: &str) {
+    let (w, h) = (framebuffer.get_width(), framebuffer.get_height());
+    let ui_scale = ui_scale(framebuffer);
+    let band = (160.0 * ui_scale) as usize;
+    let top = (h - band) / 2;
+    framebuffer.blend_rect(0, top as isize, w, band, [0x00, 0x00, 0x00, 0xFF], 0.6);
+
+    let title_style = TextStyle::new(56.0 * ui_scale).align(Align::Center);
+    framebuffer.draw_text_styled(title, w as isize / 2, top as isize + (20.0 * ui_scale) as isize, &title_style);
+    let subtitle_style = TextStyle::new(28.0 * ui_scale).align(Align::Center).color([0xDD, 0xDD, 0xDD, 0xFF]);
+    framebuffer.draw_text_styled(subtitle, w as isize / 2, top as isize + (100.0 * ui_scale) as isize, &subtitle_style);
+}
+
+// Menú en un recuadro oscuro sobre el juego: título, opciones y ayuda
+fn render_panel(framebuffer: &mut Framebuffer, title: &str, items: &[String], selected: usize, hint: &str) {
+    let (w, h) = (framebuffer.get_width(), framebuffer.get_height());
+    let ui_scale = ui_scale(framebuffer);
+    framebuffer.blend_rect(0, 0, w, h, [0x00, 0x00, 0x00, 0xFF], 0.4);
+
+    let item_height = (50.0 * ui_scale) as isize;
+    let panel_w = (w as f32 * 0.6) as usize;
+    let panel_h = (item_height * (items.len() as isize + 3)) as usize;
+    let left = (w - panel_w) as isize / 2;
+    let top = h.saturating_sub(panel_h) as isize / 2;
+    framebuffer.blend_rect(left, top, panel_w, panel_h, [0x10, 0x10, 0x20, 0xFF], 0.8);
+    framebuffer.draw_rect_outline(left, top, panel_w, panel_h, [0xFF, 0xD7, 0x00, 0xFF]);
+
+    let center = w as isize / 2;
+    let title_style = TextStyle::new(56.0 * ui_scale).align(Align::Center);
+    framebuffer.draw_text_styled(title, center, top + item_height / 4, &title_style);
+
+    let mut y = top + item_height * 3 / 2;
+    for (i, item) in items.iter().enumerate() {
+        let color = if i == selected { [0xFF, 0xD7, 0x00, 0xFF] } else { [0xFF, 0xFF, 0xFF, 0xFF] };
+        let style = TextStyle::new(34.0 * ui_scale).color(color).align(Align::Center);
+        let label = if i == selected { format!("> {} <", item) } else { item.clone() };
+        framebuffer.draw_text_styled(&label, center, y, &style);
+        y += item_height;
+    }
+
+    let hint_style = TextStyle::new(24.0 * ui_scale).align(Align::Center).color([0xDD, 0xDD, 0xDD, 0xFF]);
+    framebuffer.draw_text_styled(hint, center, y + item_height / 4, &hint_style);
+}
+
+// Aviso corto en la parte de arriba, sobre cualquier pantalla
+fn render_message(framebuffer: &mut Framebuffer, text: &str) {
+    let w = framebuffer.get_width();
+    let ui_scale = ui_scale(framebuffer);
+    let style = TextStyle::new(30.0 * ui_scale)
+        .align(Align::Center)
+        .shadow([0x00, 0x00, 0x00, 0xFF], 2)
+        .wrap(w * 9 / 10);
+    framebuffer.draw_text_styled(text, w as isize / 2, (70.0 * ui_scale) as isize, &style);
+}
+
+// Texto de ayuda en la parte inferior de las pantallas finales
+fn render_hint(framebuffer: &mut Framebuffer, hint: &str) {
+    let (w, h) = (framebuffer.get_width(), framebuffer.get_height());
+    let ui_scale = ui_scale(framebuffer);
+    let style = TextStyle::new(30.0 * ui_scale).align(Align::Center).shadow([0x00, 0x00, 0x00, 0xFF], 2);
+    framebuffer.draw_text_styled(hint, w as isize / 2, h as isize - (60.0 * ui_scale) as isize, &style);
+}
@@ -1,28 +1,83 @@
-use crate::player::Player;
-use winit::event::VirtualKeyCode;
-use winit::window::Window;
-use std::f32::consts::PI;
+// Acciones del juego, independientes del teclado concreto
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Forward,
+    Backward,
+    TurnLeft,
+    TurnRight,
+    Interact,
+    ToggleView,
+    Pause,
+    Confirm,
+    Restart,
+    NewMaze,
+    QuickSave,
+    QuickLoad,
+    MinimapZoom,
+    Automap,
+    // Solo con el automapa abierto
+    MapPanUp,
+    MapPanDown,
+    MapPanLeft,
+    MapPanRight,
+    MapZoomIn,
+    MapZoomOut,
+    MapCenter,
+    SplitView,
+    DebugOverlay,
+    Console,
+    // Solo con la consola abierta
+    HistoryPrevious,
+    HistoryNext,
+    Other, // Cualquier otra tecla (sirve para saltar la intro)
+}
 
-pub fn process_events(window: &Window, player: &mut Player) {
-    const MOVE_SPEED: f32 = 10.0;
-    const ROTATION_SPEED: f32 = PI / 10.0;
+// Nombres de las acciones en los archivos de repetición
+const ACTION_NAMES: [(Action, &str); 27] = [
+    (Action::Forward, "forward"),
+    (Action::Backward, "backward"),
+    (Action::TurnLeft, "turn_left"),
+    (Action::TurnRight, "turn_right"),
+    (Action::Interact, "interact"),
+    (Action::ToggleView, "toggle_view"),
+    (Action::Pause, "pause"),
+    (Action::Confirm, "confirm"),
+    (Action::Restart, "restart"),
+    (Action::NewMaze, "new_maze"),
+    (Action::QuickSave, "quick_save"),
+    (Action::QuickLoad, "quick_load"),
+    (Action::MinimapZoom, "minimap_zoom"),
+    (Action::Automap, "automap"),
+    (Action::MapPanUp, "map_pan_up"),
+    (Action::MapPanDown, "map_pan_down"),
+    (Action::MapPanLeft, "map_pan_left"),
+    (Action::MapPanRight, "map_pan_right"),
+    (Action::MapZoomIn, "map_zoom_in"),
+    (Action::MapZoomOut, "map_zoom_out"),
+    (Action::MapCenter, "map_center"),
+    (Action::SplitView, "split_view"),
+    (Action::DebugOverlay, "debug_overlay"),
+    (Action::Console, "console"),
+    (Action::HistoryPrevious, "history_previous"),
+    (Action::HistoryNext, "history_next"),
+    (Action::Other, "other"),
+];
 
-    if window.is_key_down(VirtualKeyCode::Left) {
-        // Rota el ángulo de vista hacia la izquierda
-        player.a -= ROTATION_SPEED;
-    }
-    if window.is_key_down(VirtualKeyCode::Right) {
-        // Rota el ángulo de vista hacia la derecha
-        player.a += ROTATION_SPEED;
-    }
-    if window.is_key_down(VirtualKeyCode::Up) {
-        // Mueve al jugador hacia adelante en la dirección de la vista
-        player.pos.x += player.a.cos() * MOVE_SPEED;
-        player.pos.y += player.a.sin() * MOVE_SPEED;
+impl Action {
+    pub fn name(self) -> &'static str {
+        ACTION_NAMES.iter().find(|(a, _)| *a == self).map(|(_, n)| *n).unwrap_or("other")
     }
-    if window.is_key_down(VirtualKeyCode::Down) {
-        // Mueve al jugador hacia atrás en la dirección de la vista
-        player.pos.x -= player.a.cos() * MOVE_SPEED;
-        player.pos.y -= player.a.sin() * MOVE_SPEED;
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ACTION_NAMES.iter().find(|(_, n)| *n == name).map(|(a, _)| *a)
     }
 }
+
+// Una entrada del jugador tal como llega al juego; es lo que se graba en las
+// repeticiones
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Input {
+    Key { action: Action, pressed: bool },
+    Look(f32),  // Giro con el ratón, ya en radianes
+    Text(char), // Carácter escrito (nombre de los récords)
+}
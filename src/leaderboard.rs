@@ -0,0 +1,111 @@
+use std::io;
+
+use crate::game::TICK_RATE;
+use crate::storage::Storage;
+
+pub const LEADERBOARD_DIR: &str = "leaderboards";
+pub const MAX_ENTRIES: usize = 10;
+pub const MAX_NAME_LEN: usize = 12;
+
+// FNV-1a de 64 bits: estable entre versiones y plataformas, a diferencia de
+// `DefaultHasher`
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// Hash del contenido de un laberinto; para una partida de varios niveles se
+// encadena pasando el hash anterior como `seed`
+pub fn hash_maze(seed: Option<u64>, maze: &[Vec<char>]) -> u64 {
+    let mut hash = seed.unwrap_or(FNV_OFFSET);
+    for row in maze {
+        for &c in row.iter().chain(['\n'].iter()) {
+            let mut bytes = [0; 4];
+            for &b in c.encode_utf8(&mut bytes).as_bytes() {
+                hash ^= b as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+    }
+    hash
+}
+
+// Tiempo en formato `m:ss.cc`
+pub fn format_ticks(ticks: u64) -> String {
+    let hundredths = ticks * 100 / TICK_RATE as u64;
+    format!("{}:{:02}.{:02}", hundredths / 6000, hundredths / 100 % 60, hundredths % 100)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub ticks: u64,
+    pub carrots: u32,
+    pub splits: Vec<u64>, // Tiempo total al terminar cada nivel
+}
+
+// Mejores tiempos de un laberinto (o de una serie de laberintos)
+#[derive(Clone, Debug)]
+pub struct Leaderboard {
+    pub key: u64,
+    pub entries: Vec<Entry>, // Ordenadas: menos tiempo primero, más zanahorias en empate
+}
+
+impl Leaderboard {
+    fn path(key: u64) -> String {
+        format!("{}/{:016x}.txt", LEADERBOARD_DIR, key)
+    }
+
+    // Si no existe el archivo la tabla está vacía; las líneas que no se
+    // entienden se saltan
+    pub fn load(storage: &mut dyn Storage, key: u64) -> Self {
+        let entries = storage
+            .read(&Self::path(key))
+            .map(|text| text.lines().filter_map(parse_entry).collect())
+            .unwrap_or_default();
+        let mut board = Self { key, entries };
+        board.entries.sort_by(entry_order);
+        board.entries.truncate(MAX_ENTRIES);
+        board
+    }
+
+    pub fn save(&self, storage: &mut dyn Storage) -> io::Result<()> {
+        let mut text = String::from("# tiempo_en_ticks zanahorias parciales nombre\n");
+        for entry in &self.entries {
+            let splits: Vec<String> = entry.splits.iter().map(u64::to_string).collect();
+            text += &format!("{} {} {} {}\n", entry.ticks, entry.carrots, splits.join(","), entry.name);
+        }
+        storage.write(&Self::path(self.key), &text)
+    }
+
+    // Posición que tendría este resultado, si entra en la tabla
+    pub fn rank_for(&self, ticks: u64, carrots: u32) -> Option<usize> {
+        let probe = Entry { name: String::new(), ticks, carrots, splits: Vec::new() };
+        let rank = self.entries.iter().position(|e| entry_order(&probe, e).is_lt()).unwrap_or(self.entries.len());
+        (rank < MAX_ENTRIES).then_some(rank)
+    }
+
+    pub fn insert(&mut self, entry: Entry) -> Option<usize> {
+        let rank = self.rank_for(entry.ticks, entry.carrots)?;
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+}
+
+fn entry_order(a: &Entry, b: &Entry) -> std::cmp::Ordering {
+    a.ticks.cmp(&b.ticks).then(b.carrots.cmp(&a.carrots))
+}
+
+fn parse_entry(line: &str) -> Option<Entry> {
+    if line.starts_with('#') {
+        return None;
+    }
+    let mut parts = line.splitn(4, ' ');
+    let ticks = parts.next()?.parse().ok()?;
+    let carrots = parts.next()?.parse().ok()?;
+    let splits = match parts.next()? {
+        "" => Vec::new(),
+        splits => splits.split(',').map(str::parse).collect::<Result<_, _>>().ok()?,
+    };
+    let name = parts.next()?.trim().to_string();
+    Some(Entry { name, ticks, carrots, splits })
+}
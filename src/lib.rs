@@ -0,0 +1,26 @@
+pub mod audio;
+#[cfg(feature = "audio")]
+pub mod audio_rodio;
+pub mod automap;
+pub mod capture;
+pub mod cast_ray;
+pub mod console;
+pub mod debug_overlay;
+pub mod door;
+pub mod framebuffer;
+pub mod game;
+pub mod input;
+pub mod leaderboard;
+pub mod maze;
+pub mod minimap;
+pub mod player;
+pub mod profiler;
+pub mod render;
+pub mod replay;
+pub mod save;
+pub mod scaling;
+pub mod settings;
+pub mod storage;
+pub mod text;
+pub mod texture;
+pub mod world;
@@ -1,51 +1,44 @@
-use nalgebra_glm::Vec2;
 use pixels::{Pixels, SurfaceTexture};
 use std::time::{Duration, Instant};
-use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
+use winit::event::{ElementState, Event, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::WindowBuilder;
-use image::io::Reader as ImageReader;
-use image::GenericImageView;
-use rodio::{Decoder, OutputStream, Sink, Source}; // Para manejar el audio
+use winit::dpi::PhysicalSize;
+use winit::window::{Fullscreen, WindowBuilder};
+
+use raycaster::audio::AudioManager;
+use raycaster::capture::{save_screenshot, FrameCapture, SCREENSHOT_DIR};
+use raycaster::console::CONSOLE_SCRIPT;
+use raycaster::framebuffer::Framebuffer;
+use raycaster::game::{Game, GameEvent, TICK_DT};
+use raycaster::input::{Action, Input};
+use raycaster::profiler::{self, Phase};
+use raycaster::render::set_render_threads;
+use raycaster::scaling::{fit_rect, upscale, RenderResolution, ScaleFilter};
+use raycaster::settings::{Settings, SETTINGS_FILE};
+use raycaster::storage::FileStorage;
+use raycaster::world::{WORLD_HEIGHT, WORLD_WIDTH};
 
-mod framebuffer;
-mod cast_ray;
-mod maze;
-mod player;
-mod texture;
-
-use cast_ray::{cast_ray, Intersect};
-use framebuffer::Framebuffer;
-use maze::load_maze;
-use player::Player;
-use rusttype::{Font, Scale};
-use once_cell::sync::Lazy;
-use std::sync::Arc;
-use std::fs::File;
-
-use texture::Texture;
-
-// Texturas de las paredes
-static WALL1: Lazy<Arc<Texture>> = Lazy::new(|| Arc::new(Texture::new("assets/cerca3a.png")));
-static WALL2: Lazy<Arc<Texture>> = Lazy::new(|| Arc::new(Texture::new("assets/puerta3.png")));
-static CARROT: Lazy<Arc<Texture>> = Lazy::new(|| Arc::new(Texture::new("assets/Z3.png")));
-static CAT_TEXTURE: Lazy<Arc<Texture>> = Lazy::new(|| Arc::new(Texture::new("assets/sprite/gatoM.png")));
-
-fn render_image(framebuffer: &mut Framebuffer, img: &image::DynamicImage) {
-    let (img_width, img_height) = img.dimensions();
-    for y in 0..img_height {
-        for x in 0..img_width {
-            let pixel = img.get_pixel(x, y);
-            framebuffer.point(x as usize, y as usize, [pixel[0], pixel[1], pixel[2], pixel[3]]);
-        }
+fn main() {
+    // Tamaño lógico del mundo y de la ventana inicial; el render interno
+    // puede tener otra resolución (`--resolution`)
+    let width = WORLD_WIDTH;
+    let height = WORLD_HEIGHT;
+
+    // Hilos del render 3D: `--threads N` (por defecto uno por núcleo)
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(threads) = arg_value(&args, "--threads") {
+        let threads = threads.parse().expect("--threads espera un número");
+        set_render_threads(threads).expect("No se pudo crear el pool de render");
     }
-}
 
-fn main() {
-    let width = 1300;
-    let height = 900;
-    let width_framebuffer = 1300;
-    let height_framebuffer = 900;
+    // Opciones guardadas; `--resolution 320x200` (o `native`) cambia la resolución interna
+    let mut settings = Settings::load(SETTINGS_FILE);
+    if let Some(resolution) = arg_value(&args, "--resolution") {
+        settings.resolution = RenderResolution::parse(resolution).expect("--resolution espera ANCHOxALTO o native");
+    }
+    let mut scale_filter = arg_value(&args, "--filter")
+        .map(|f| ScaleFilter::parse(f).expect("--filter espera nearest o linear"))
+        .unwrap_or(ScaleFilter::Nearest);
 
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new()
@@ -53,88 +46,142 @@ fn main() {
         .with_inner_size(winit::dpi::LogicalSize::new(width as f64, height as f64))
         .build(&event_loop)
         .unwrap();
+    if args.iter().any(|a| a == "--fullscreen") {
+        window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+    }
 
+    // El buffer de `pixels` tiene el tamaño real de la ventana; la imagen
+    // interna se amplía sobre él con `upscale`
+    let mut window_size = window.inner_size();
     let mut pixels = {
-        let window_size = window.inner_size();
         let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, &window);
-        Pixels::new(width as u32, height as u32, surface_texture).unwrap()
+        Pixels::new(window_size.width, window_size.height, surface_texture).unwrap()
     };
+    let mut scene_buffer: Vec<u8> = Vec::new();
+
+    // Sin dispositivo de salida (o con `--no-audio`) el juego sigue en silencio
+    let mut audio = if args.iter().any(|a| a == "--no-audio") { AudioManager::null() } else { AudioManager::open_default() };
+    audio.apply_settings(&settings);
+    audio.start();
+
+    // Niveles: `--level archivo` se puede repetir; por defecto `maze.txt`
+    let mut levels: Vec<String> = args
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| *a == "--level")
+        .filter_map(|(i, _)| args.get(i + 1).cloned())
+        .collect();
+    if levels.is_empty() {
+        levels.push("maze.txt".to_string());
+    }
+    // Semilla de los laberintos generados: `--seed N` (por defecto, la hora)
+    let seed = match arg_value(&args, "--seed") {
+        Some(seed) => seed.parse().expect("--seed espera un número"),
+        None => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+    let mut game = Game::new(levels, seed, settings);
+    game.set_storage(Box::new(FileStorage::default()));
+
+    // `--record archivo`: graba las entradas para repetir la partida con `replay`.
+    // Empieza antes de las órdenes de arranque para que también queden grabadas
+    let record_path = arg_value(&args, "--record").map(str::to_string);
+    if record_path.is_some() {
+        game.start_recording();
+    }
 
-    // Cargar la imagen `inicio.gif`
-    let img = ImageReader::open("assets/inicio.gif")
-        .unwrap()
-        .decode()
-        .unwrap();
-    // Load the victory screen image `Fin.png`
-    let fin_img = ImageReader::open("assets/Fin.png")
-        .unwrap()
-        .decode()
-        .unwrap();
-    // Cargar la imagen de derrota `Perdio.png`
-    let perdio_img = ImageReader::open("assets/sprite//Perdio.png")
-    .unwrap()
-    .decode()
-    .unwrap();
-
-    let mut player_lost = false;  // Variable para determinar si el jugador ha perdido
-
-    //--------------------
-    // Cargar la música de caminar "Walking_Forest.mp3"
-    let sound_file = File::open("assets/sounds/Walking_Forest.mp3").unwrap();
-    let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-    let sink_walk = Sink::try_new(&stream_handle).unwrap();
-    let sound_decoder = Decoder::new(sound_file).unwrap();
-    sink_walk.append(sound_decoder.repeat_infinite());
-    sink_walk.pause(); // El sonido de caminar empieza en pausa
-
-    // **Controlar el volumen del sonido de pasos**:
-    sink_walk.set_volume(0.9); // Volumen de pasos al 90%
-
-    // Cargar el archivo de sonido de fondo "Jumpin_June.mp3"
-    let music_file = File::open("assets/sounds/Jumpin_June.mp3").unwrap();
-    let music_sink = Sink::try_new(&stream_handle).unwrap();
-    let music_decoder = Decoder::new(music_file).unwrap();
-    music_sink.append(music_decoder.repeat_infinite());
-    music_sink.play(); // Iniciar la música desde el comienzo
-
-    // **Controlar el volumen de la música de fondo**:
-    music_sink.set_volume(0.1); // Volumen de la música de fondo al 10%
-
-    //-------------------
-
-    //let maze = load_maze("maze.txt");
-    let mut maze = load_maze("maze.txt");
-
-    let block_size_x = width / maze[0].len();
-    let block_size_y = height / maze.len();
-    let block_size = block_size_x.min(block_size_y);
+    // Órdenes de consola al arrancar: `--exec archivo`, o `autoexec.cfg` si existe
+    match arg_value(&args, "--exec") {
+        Some(path) => game.run_startup_script(path).unwrap_or_else(|err| panic!("No se pudo leer {}: {}", path, err)),
+        None => {
+            if let Err(err) = game.run_startup_script(CONSOLE_SCRIPT) {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    eprintln!("No se pudo leer {}: {}", CONSOLE_SCRIPT, err);
+                }
+            }
+        }
+    }
 
-    let player_pos = Vec2::new(100.0 + block_size as f32, 100.0 + block_size as f32);
-    let player_fov = std::f32::consts::PI / 3.0;
-    let mut player = Player::new(player_pos, std::f32::consts::PI / 3.0, player_fov);
+    // Capturas: `F12` o `--screenshot-at TICK` guardan la imagen actual en
+    // `screenshots/`; `--capture carpeta` (o `clip.gif`) graba cada fotograma
+    let mut screenshot_at: Option<u64> = arg_value(&args, "--screenshot-at")
+        .map(|t| t.parse().expect("--screenshot-at espera un número de tick"));
+    let mut capture = arg_value(&args, "--capture")
+        .map(|path| FrameCapture::create(path).unwrap_or_else(|err| panic!("No se pudo empezar a grabar en {}: {}", path, err)));
+    let mut last_capture = Instant::now();
 
-    let mut score = 0;
-    let mut mode = "2D";
     let mut last_mouse_x = width as f64 / 2.0;
-    let mouse_sensitivity = 0.005;
+    let mut fb_size = (width, height); // Tamaño del último fotograma, para situar el ratón sobre él
     let mut last_frame_time = Instant::now();
+    let mut last_update = Instant::now();
+    let mut tick_accumulator = 0.0;
     let mut frame_count = 0;
     let mut fps = 0;
     let fps_update_interval = Duration::from_secs(1);
-    let mut show_intro = true;
-    let mut game_won = false; // Track game state (won or not)
-
-    // **Configuración de la animación del gato**
-    let mut cat_pos = Vec2::new(299.13, 91.41);  // Posición inicial
-    let cat_end_pos = Vec2::new(299.13, 120.41); // Posición final
-    let mut cat_direction = 1.0;                 // Dirección del movimiento (1 = hacia adelante, -1 = hacia atrás)
-    let cat_speed = 0.05;                        // Velocidad de movimiento
-    
 
     event_loop.run(move |event, _, control_flow| {
         match event {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(size) => {
+                    resize_pixels(&mut pixels, &mut window_size, size);
+                }
+                WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                    resize_pixels(&mut pixels, &mut window_size, *new_inner_size);
+                }
+                WindowEvent::KeyboardInput {
+                    input: KeyboardInput {
+                        virtual_keycode: Some(VirtualKeyCode::F11),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                    ..
+                } => {
+                    // Pantalla completa sin bordes
+                    let fullscreen = match window.fullscreen() {
+                        Some(_) => None,
+                        None => Some(Fullscreen::Borderless(None)),
+                    };
+                    window.set_fullscreen(fullscreen);
+                }
+                WindowEvent::KeyboardInput {
+                    input: KeyboardInput {
+                        virtual_keycode: Some(VirtualKeyCode::F2),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                    ..
+                } => {
+                    game.settings.resolution = game.settings.resolution.next();
+                    window.set_title(&format!("Rust Graphics - Maze Example ({})", game.settings.resolution.label()));
+                    save_settings(&game.settings);
+                }
+                WindowEvent::KeyboardInput {
+                    input: KeyboardInput {
+                        virtual_keycode: Some(VirtualKeyCode::F3),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                    ..
+                } => {
+                    scale_filter = match scale_filter {
+                        ScaleFilter::Nearest => ScaleFilter::Linear,
+                        ScaleFilter::Linear => ScaleFilter::Nearest,
+                    };
+                }
+                WindowEvent::KeyboardInput {
+                    input: KeyboardInput {
+                        virtual_keycode: Some(VirtualKeyCode::F12),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                    ..
+                } => {
+                    // Se guarda en el próximo fotograma, cuando ya está dibujado
+                    screenshot_at = Some(game.ticks);
+                }
                 WindowEvent::KeyboardInput {
                     input: KeyboardInput {
                         virtual_keycode: Some(keycode),
@@ -143,150 +190,113 @@ fn main() {
                     },
                     ..
                 } => {
-                    if show_intro && state == ElementState::Pressed {
-                        show_intro = false;
-                    } else if !show_intro {
-                        let mut new_pos = player.pos;
-                        let mut moving = false;
-
-                        // Verificar si se presiona una de las teclas W, A, S o D
-                        match (keycode, state) {
-                            (VirtualKeyCode::Left | VirtualKeyCode::A, ElementState::Pressed) => {
-                                player.a -= std::f32::consts::PI / 10.0;
-                                moving = true;
-                            }
-                            (VirtualKeyCode::Right | VirtualKeyCode::D, ElementState::Pressed) => {
-                                player.a += std::f32::consts::PI / 10.0;
-                                moving = true;
-                            }
-                            (VirtualKeyCode::Up | VirtualKeyCode::W, ElementState::Pressed) => {
-                                new_pos.x += player.a.cos() * 10.0;
-                                new_pos.y += player.a.sin() * 10.0;
-                                moving = true;
-                            }
-                            (VirtualKeyCode::Down | VirtualKeyCode::S, ElementState::Pressed) => {
-                                new_pos.x -= player.a.cos() * 10.0;
-                                new_pos.y -= player.a.sin() * 10.0;
-                                moving = true;
-                            }
-                            (VirtualKeyCode::M, ElementState::Pressed) => {
-                                mode = if mode == "2D" { "3D" } else { "2D" };
-                                window.request_redraw();
-                            }
-                            _ => {}
-                        }
-
-                        //if !check_collision(new_pos, &maze, block_size) {
-                        //    player.pos = new_pos;
-                        //}
-
-
-                        // Check if the player reached the goal 'g'
-                        let collision = check_collision(new_pos, &maze, block_size);
-                        if collision == 'g' {
-                            game_won = true;
-                        } else if collision == ' ' {
-                            player.pos = new_pos;
-                        } else if collision == 'z' {
-                            player.pos = new_pos;
-                            score += 1;
-                            maze[new_pos.y as usize / block_size][new_pos.x as usize / block_size] = ' ';
-                        }
-
-                        // Verificar colisión con el gato
-                        let distance_to_cat = (player.pos - cat_pos).magnitude();
-                        if distance_to_cat < block_size as f32 {
-                            player_lost = true;  // Marcar que el jugador ha perdido
-                        }
-
-
-                        if moving {
-                            sink_walk.play();
-                        } else {
-                            sink_walk.pause();
-                        }
-
-                        if state == ElementState::Released {
-                            sink_walk.pause();
-                        }
-                    }
+                    let action = if game.console_open() {
+                        console_action_for_key(keycode)
+                    } else if game.entering_name() {
+                        name_action_for_key(keycode)
+                    } else {
+                        action_for_key(keycode)
+                    };
+                    game.apply(Input::Key { action, pressed: state == ElementState::Pressed });
                 }
+                WindowEvent::ReceivedCharacter(c) => game.apply(Input::Text(c)),
                 WindowEvent::CursorMoved { position, .. } => {
-                    if !show_intro {
-                        let mouse_x = position.x;
-                        let delta_x = mouse_x - last_mouse_x;
-                        player.a += (delta_x as f32) * (mouse_sensitivity as f32);
-                        last_mouse_x = mouse_x;
+                    let mouse_x = position.x;
+                    let delta_x = mouse_x - last_mouse_x;
+                    last_mouse_x = mouse_x;
+                    if game.split_view() {
+                        // La imagen puede tener bandas negras a los lados: se mide sobre ella
+                        let (off_x, off_y, out_w, out_h) =
+                            fit_rect(fb_size.0, fb_size.1, window_size.width as usize, window_size.height as usize);
+                        let u = (position.x as f32 - off_x as f32) / out_w as f32;
+                        let v = (position.y as f32 - off_y as f32) / out_h as f32;
+                        let inside = (0.0..1.0).contains(&u) && (0.0..1.0).contains(&v);
+                        game.set_pointer(inside.then_some((u, v)));
+                    } else {
+                        game.apply(Input::Look((delta_x as f32) * game.settings.mouse_sensitivity));
                     }
                 }
+                WindowEvent::CursorLeft { .. } => game.set_pointer(None),
+                WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } => game.click(),
                 _ => {}
             },
             Event::RedrawRequested(_) => {
-                let frame = pixels.get_frame();
-                let mut framebuffer = Framebuffer::new(width_framebuffer, height_framebuffer, frame);
-                framebuffer.clear([0, 0, 0, 0xFF]);
-
-                if show_intro {
-                    render_image(&mut framebuffer, &img);
-                } else if game_won {
-                    // Show the "Fin.png" screen when the player wins
-                    render_image(&mut framebuffer, &fin_img);
-
-                    
-                } else if player_lost {
-                    // Mostrar la pantalla de que el jugador perdió
-                    render_image(&mut framebuffer, &perdio_img);
-
-                } else {
-                    if mode == "2D" {
-                        render2d(&mut framebuffer, &player, &maze, width, height, block_size);
-                    } else {
-                        render3d(&mut framebuffer, &player, block_size, &maze);
-                        let distance_to_projection_plane = width_framebuffer as f32 / 2.0 / (player.fov / 2.0).tan();
-                                                //(299.13,91.41)
-                        //(299.13,120.41)
-
-
-                        //let cat_pos = Vec2::new(300.0, 200.0);  // Posición del gato en el mundo
-                        //let cat_pos = Vec2::new(300.26, 218.68);  // Ajusta esta posición según tu laberinto
-                        //let cat_pos = Vec2::new(299.13,91.41);  // Ajusta esta posición según tu laberinto
-
-                        // **Animación del gato**
-                        // Mover el gato entre las dos posiciones
-                        cat_pos.y += cat_speed * cat_direction;
-
-                        // Invertir la dirección cuando llega a los extremos
-                        if cat_pos.y >= cat_end_pos.y || cat_pos.y <= 91.41 {
-                            cat_direction *= -1.0;
-                        }
+                // Paso fijo: tantos ticks como quepan en el tiempo transcurrido, con
+                // un máximo para no quedarse atrás después de una pausa larga
+                let now = Instant::now();
+                let frame_dt = (now - last_update).as_secs_f32();
+                tick_accumulator = (tick_accumulator + frame_dt).min(TICK_DT * 10.0);
+                last_update = now;
+                while tick_accumulator >= TICK_DT {
+                    game.tick();
+                    tick_accumulator -= TICK_DT;
+                }
+                audio.update(&game, frame_dt);
+
+                let (window_width, window_height) = (window_size.width as usize, window_size.height as usize);
+                let (fb_width, fb_height) = game.settings.resolution.size(window_width, window_height);
+                fb_size = (fb_width, fb_height);
+                scene_buffer.resize(fb_width * fb_height * 4, 0);
+                let mut framebuffer = Framebuffer::new(fb_width, fb_height, &mut scene_buffer);
+                {
+                    let _timer = profiler::time(Phase::Clear);
+                    framebuffer.clear([0, 0, 0, 0xFF]);
+                }
 
-                        render_cat_sprite(
-                            &mut framebuffer, 
-                            &player, 
-                            cat_pos, 
-                            distance_to_projection_plane, 
-                            block_size, 
-                            &maze, 
-                            300.0
-                        );
-                    
+                frame_count += 1;
+                if last_frame_time.elapsed() >= fps_update_interval {
+                    fps = frame_count;
+                    frame_count = 0;
+                    last_frame_time = Instant::now();
+                }
+                game.render(&mut framebuffer, fps);
 
+                if screenshot_at.is_some_and(|tick| game.ticks >= tick) {
+                    screenshot_at = None;
+                    match save_screenshot(SCREENSHOT_DIR.as_ref(), &scene_buffer, fb_width, fb_height) {
+                        Ok(path) => println!("Captura guardada en {}", path.display()),
+                        Err(err) => eprintln!("No se pudo guardar la captura: {}", err),
                     }
-
-                    frame_count += 1;
-                    if last_frame_time.elapsed() >= fps_update_interval {
-                        fps = frame_count;
-                        frame_count = 0;
-                        last_frame_time = Instant::now();
+                }
+                if let Some(frames) = &mut capture {
+                    let now = Instant::now();
+                    if let Err(err) = frames.add_frame(&scene_buffer, fb_width, fb_height, now - last_capture) {
+                        eprintln!("Se detiene la grabación de fotogramas: {}", err);
+                        capture = None;
                     }
-
-                    //render_text(&mut framebuffer, &format!("FPS: {}", fps), 10, 10, 40.0);
-                    framebuffer.draw_text(&format!("FPS: {}", fps), 10, 10, 40.0);
-                    framebuffer.draw_text(&format!("Puntos: {}", score), width - 150, 10, 30.0);
+                    last_capture = now;
                 }
 
-                if pixels.render().is_err() {
-                    *control_flow = ControlFlow::Exit;
+                {
+                    let _timer = profiler::time(Phase::Present);
+                    upscale(&scene_buffer, fb_width, fb_height, pixels.get_frame(), window_width, window_height, scale_filter);
+                    if pixels.render().is_err() {
+                        *control_flow = ControlFlow::Exit;
+                    }
+                }
+                profiler::end_frame(frame_dt * 1000.0);
+
+                for event in game.drain_events() {
+                    audio.handle_event(&event, &game.world);
+                    match event {
+                        GameEvent::SettingsChanged => {
+                            audio.apply_settings(&game.settings);
+                            save_settings(&game.settings);
+                        }
+                        GameEvent::SaveFailed { error, .. } => eprintln!("{}", error),
+                        GameEvent::Quit => *control_flow = ControlFlow::Exit,
+                        _ => {}
+                    }
+                }
+            }
+            Event::LoopDestroyed => {
+                // Al soltar el GIF se escribe el final del archivo
+                capture.take();
+                if let (Some(path), Some(replay)) = (&record_path, game.finish_recording()) {
+                    match replay.save(path) {
+                        Ok(()) => println!("Repetición guardada en {} ({} ticks)", path, replay.ticks),
+                        Err(err) => eprintln!("No se pudo guardar la repetición en {}: {}", path, err),
+                    }
                 }
             }
             Event::MainEventsCleared => {
@@ -297,235 +307,75 @@ fn main() {
     });
 }
 
-fn render_text(framebuffer: &mut Framebuffer, text: &str, x: usize, y: usize, scale: f32) {
-    let font_data = include_bytes!("../assets/Typold-Book500.ttf");
-    let font = Font::try_from_bytes(font_data as &[u8]).unwrap();
-    let scale = Scale::uniform(scale);
-    let v_metrics = font.v_metrics(scale);
-    let offset = rusttype::point(x as f32, y as f32 + v_metrics.ascent);
-    let glyphs: Vec<_> = font.layout(text, scale, offset).collect();
-
-    for glyph in glyphs {
-        if let Some(bounding_box) = glyph.pixel_bounding_box() {
-            glyph.draw(|gx, gy, v| {
-                let x = (gx as i32 + bounding_box.min.x) as usize;
-                let y = (gy as i32 + bounding_box.min.y) as usize;
-                if x < framebuffer.width && y < framebuffer.height {
-                    let intensity = (v * 255.0) as u8;
-                    framebuffer.point(x, y, [intensity, intensity, intensity, 255]);
-                }
-            });
-        }
+fn save_settings(settings: &Settings) {
+    if let Err(err) = settings.save(SETTINGS_FILE) {
+        eprintln!("No se pudieron guardar las opciones en {}: {}", SETTINGS_FILE, err);
     }
 }
 
-fn render2d(
-    framebuffer: &mut framebuffer::Framebuffer,
-    player: &Player,
-    maze: &Vec<Vec<char>>,
-    width: usize,
-    height: usize,
-    block_size: usize,
-) {
-    for (row_idx, row) in maze.iter().enumerate() {
-        for (col_idx, &cell) in row.iter().enumerate() {
-            let color = match cell {
-                '+' => WALL1.get_pixel_color(0, 0),
-                '-' => WALL1.get_pixel_color(0, 0),
-                '|' => WALL1.get_pixel_color(0, 0),
-                'g' => WALL1.get_pixel_color(0, 0),
-                'z' => CARROT.get_pixel_color(0, 0), // Nueva condición para la zanahoria
-                ' ' => [0xFF, 0xD7, 0xB3, 0xFF],
-                'p' => [0x00, 0xFF, 0x00, 0xFF],
-                _ => [0x00, 0x00, 0x00, 0xFF],
-            };
-
-            framebuffer.draw_rect(col_idx * block_size, row_idx * block_size, block_size, block_size, color);
-        }
-    }
-
-    let num_rays = width;
-    for i in 0..num_rays {
-        let current_ray = i as f32 / num_rays as f32;
-        let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
-        cast_ray(framebuffer, &maze, &player, a, block_size, true);
-    }
+// Valor que sigue a `flag` en la línea de comandos
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(|s| s.as_str())
 }
 
-fn render3d(
-    framebuffer: &mut framebuffer::Framebuffer,
-    player: &Player,
-    block_size: usize,
-    maze: &Vec<Vec<char>>,
-) {
-    let num_rays = framebuffer.get_width();
-    let hw = framebuffer.get_width() as f32 / 2.0;
-    let hh = framebuffer.get_height() as f32 / 2.0;
-    let distance_to_projection_plane = hw / (player.fov / 2.0).tan();
-
-    framebuffer.draw_sky_and_ground();
-
-    for i in 0..num_rays {
-        let current_ray = i as f32 / num_rays as f32;
-        let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
-
-        let intersect = cast_ray(framebuffer, maze, &player, a, block_size, false);
-        let distance_to_wall = intersect.distance;
-
-        if distance_to_wall < 0.01 {
-            continue;
-        }
-
-        let stake_height = (distance_to_projection_plane / distance_to_wall) * block_size as f32;
-        let stake_top = (hh - (stake_height / 2.0)) as isize;
-        let stake_bottom = (hh + (stake_height / 2.0)) as isize;
-
-        let stake_top = stake_top.max(0) as usize;
-        let stake_bottom = stake_bottom.min(framebuffer.get_height() as isize) as usize;
-
-        for y in stake_top..stake_bottom {
-            let ty = ((y as f32 - stake_top as f32) / (stake_bottom as f32 - stake_top as f32) * 128.0) as u32;
-            let color = match intersect.impact {
-                '+' => WALL1.get_pixel_color(intersect.tx.try_into().unwrap(), ty),
-                '-' => WALL1.get_pixel_color(intersect.tx.try_into().unwrap(), ty),
-                '|' => WALL1.get_pixel_color(intersect.tx.try_into().unwrap(), ty),
-                'g' => WALL2.get_pixel_color(intersect.tx.try_into().unwrap(), ty),
-                'z' => CARROT.get_pixel_color(intersect.tx.try_into().unwrap(), ty), // Textura de zanahoria
-
-                _ => [0x00, 0x00, 0x00, 0xFF],
-            };
-
-            framebuffer.point(i, y, color);
-        }
+// Ajusta la superficie y el buffer de `pixels` al nuevo tamaño de la ventana
+fn resize_pixels(pixels: &mut Pixels, window_size: &mut PhysicalSize<u32>, size: PhysicalSize<u32>) {
+    if size.width == 0 || size.height == 0 {
+        return; // Ventana minimizada
     }
-
-    let minimap_size = 200;
-    render_minimap(framebuffer, player, maze, minimap_size, block_size);
+    *window_size = size;
+    pixels.resize_surface(size.width, size.height);
+    pixels.resize_buffer(size.width, size.height);
 }
-fn render_minimap(
-    framebuffer: &mut framebuffer::Framebuffer,
-    player: &Player,
-    maze: &Vec<Vec<char>>,
-    minimap_size: usize,
-    block_size: usize,
-) {
-    let rows = maze.len();
-    let cols = maze[0].len();
-
-    let scale_factor_x = minimap_size as f32 / cols as f32;
-    let scale_factor_y = minimap_size as f32 / rows as f32;
-
-    for (row_idx, row) in maze.iter().enumerate() {
-        for (col_idx, &cell) in row.iter().enumerate() {
-            let color = match cell {
-                ' ' => [0xFF, 0xD7, 0xB3, 0xFF],
-                'p' => [0x00, 0xFF, 0x00, 0xFF],
-                'g' => [0xFF, 0x00, 0x00, 0xFF],
-                _ => [0x00, 0x00, 0x00, 0xFF],
-            };
 
-            let x = (col_idx as f32 * scale_factor_x) as usize;
-            let y = (row_idx as f32 * scale_factor_y) as usize;
-
-            framebuffer.draw_rect(
-                framebuffer.get_width() - minimap_size + x,
-                framebuffer.get_height() - minimap_size + y,
-                scale_factor_x.ceil() as usize,
-                scale_factor_y.ceil() as usize,
-                color,
-            );
-        }
+// Tecla de la ventana a acción del juego
+fn action_for_key(key: VirtualKeyCode) -> Action {
+    match key {
+        VirtualKeyCode::Up | VirtualKeyCode::W => Action::Forward,
+        VirtualKeyCode::Down | VirtualKeyCode::S => Action::Backward,
+        VirtualKeyCode::Left | VirtualKeyCode::A => Action::TurnLeft,
+        VirtualKeyCode::Right | VirtualKeyCode::D => Action::TurnRight,
+        VirtualKeyCode::E => Action::Interact,
+        VirtualKeyCode::M => Action::ToggleView,
+        VirtualKeyCode::Escape => Action::Pause,
+        VirtualKeyCode::Return | VirtualKeyCode::Space => Action::Confirm,
+        VirtualKeyCode::R => Action::Restart,
+        VirtualKeyCode::N => Action::NewMaze,
+        VirtualKeyCode::F5 => Action::QuickSave,
+        VirtualKeyCode::F9 => Action::QuickLoad,
+        VirtualKeyCode::Tab => Action::MinimapZoom,
+        VirtualKeyCode::Q => Action::Automap,
+        VirtualKeyCode::I => Action::MapPanUp,
+        VirtualKeyCode::K => Action::MapPanDown,
+        VirtualKeyCode::J => Action::MapPanLeft,
+        VirtualKeyCode::L => Action::MapPanRight,
+        VirtualKeyCode::Equals | VirtualKeyCode::Plus | VirtualKeyCode::NumpadAdd => Action::MapZoomIn,
+        VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => Action::MapZoomOut,
+        VirtualKeyCode::C => Action::MapCenter,
+        VirtualKeyCode::F6 => Action::SplitView,
+        VirtualKeyCode::F4 => Action::DebugOverlay,
+        VirtualKeyCode::Grave => Action::Console,
+        _ => Action::Other,
     }
-
-    let player_x = (player.pos.x / block_size as f32 * scale_factor_x) as usize;
-    let player_y = (player.pos.y / block_size as f32 * scale_factor_y) as usize;
-    framebuffer.draw_rect(
-        framebuffer.get_width() - minimap_size + player_x,
-        framebuffer.get_height() - minimap_size + player_y,
-        3,
-        3,
-        [0x00, 0xFF, 0x00, 0xFF],
-    );
-}
-
-fn check_collision(pos: Vec2, maze: &Vec<Vec<char>>, block_size: usize) -> char {
-    let i = pos.x as usize / block_size;
-    let j = pos.y as usize / block_size;
-    maze[j][i]
-}
-fn check_cat_collision(cat_pos: Vec2, maze: &Vec<Vec<char>>, block_size: usize) -> bool {
-    let i = (cat_pos.x / block_size as f32) as usize;
-    let j = (cat_pos.y / block_size as f32) as usize;
-    
-    // Verifica si la celda actual es una pared
-    maze[j][i] == '+' || maze[j][i] == '-' || maze[j][i] == '|'
 }
 
-fn render_cat_sprite(
-    framebuffer: &mut framebuffer::Framebuffer,
-    player: &Player,
-    cat_pos: Vec2,  // Posición del gato
-    distance_to_projection_plane: f32,
-    block_size: usize,
-    maze: &Vec<Vec<char>>,  // Referencia al laberinto
-    max_distance: f32,  // Distancia máxima de visibilidad
-) {
-    // Verificar si el gato está colisionando con una pared
-    if check_cat_collision(cat_pos, maze, block_size) {
-        return;  // Si el gato está en una pared, no se renderiza
-    }
-
-    let direction = cat_pos - player.pos;
-    let distance = direction.magnitude();
-
-    // Evitar que el gato se vea si está más allá de la distancia máxima
-    if distance > max_distance {
-        return;
-    }
-
-    // Verificar si hay una pared entre el jugador y el gato (línea de visión)
-    if !has_line_of_sight(&player.pos, &cat_pos, maze, block_size) {
-        return;
-    }
-
-    let angle_to_player = direction.y.atan2(direction.x) - player.a;
-
-    let sprite_height = (distance_to_projection_plane / distance) * block_size as f32;
-    let sprite_top = (framebuffer.get_height() as f32 / 2.0 - sprite_height / 2.0).max(0.0) as usize;
-    let sprite_bottom = (framebuffer.get_height() as f32 / 2.0 + sprite_height / 2.0).min(framebuffer.get_height() as f32) as usize;
-
-    let sprite_width = sprite_height;  // Suponiendo que el sprite es cuadrado
-    let sprite_left = (framebuffer.get_width() as f32 / 2.0 + angle_to_player * distance_to_projection_plane).max(0.0) as usize;
-    let sprite_right = (sprite_left as f32 + sprite_width).min(framebuffer.get_width() as f32) as usize;
-
-    for y in sprite_top..sprite_bottom {
-        for x in sprite_left..sprite_right {
-            let tx = ((x - sprite_left) as f32 / (sprite_right - sprite_left) as f32 * CAT_TEXTURE.width as f32) as u32;
-            let ty = ((y - sprite_top) as f32 / (sprite_bottom - sprite_top) as f32 * CAT_TEXTURE.height as f32) as u32;
-            let color = CAT_TEXTURE.get_pixel_color(tx, ty);
-
-            framebuffer.point(x, y, color);
-        }
+// Al escribir el nombre de un récord el espacio es una letra más: solo `Enter`
+// lo guarda y `Esc` lo salta
+fn name_action_for_key(key: VirtualKeyCode) -> Action {
+    match key {
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => Action::Confirm,
+        VirtualKeyCode::Escape => Action::Pause,
+        _ => Action::Other,
     }
 }
 
-
-fn has_line_of_sight(start_pos: &Vec2, target_pos: &Vec2, maze: &Vec<Vec<char>>, block_size: usize) -> bool {
-    let delta = *target_pos - *start_pos;
-    let steps = delta.magnitude() as usize;
-    let step = delta / steps as f32;
-
-    let mut current_pos = *start_pos;
-    for _ in 0..steps {
-        current_pos += step;
-
-        // Verificar si la posición actual está dentro de una pared
-        let i = (current_pos.x / block_size as f32) as usize;
-        let j = (current_pos.y / block_size as f32) as usize;
-        if maze[j][i] != ' ' {
-            return false;  // Hay una pared bloqueando la línea de visión
-        }
+// Con la consola abierta las letras solo escriben (llegan como `Input::Text`);
+// las flechas recorren el historial
+fn console_action_for_key(key: VirtualKeyCode) -> Action {
+    match key {
+        VirtualKeyCode::Up => Action::HistoryPrevious,
+        VirtualKeyCode::Down => Action::HistoryNext,
+        VirtualKeyCode::Grave | VirtualKeyCode::Escape => Action::Console,
+        _ => Action::Other,
     }
-
-    true  // No hay obstrucciones en la línea de visión
 }
@@ -1,6 +1,11 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
+use crate::door::{is_door_tile, KeyColor};
+
 pub fn load_maze(filename: &str) -> Vec<Vec<char>> {
     let file = File::open(filename).expect("Unable to open file");
     let reader = BufReader::new(file);
@@ -10,3 +15,220 @@ pub fn load_maze(filename: &str) -> Vec<Vec<char>> {
         .map(|line| line.expect("Could not parse line").chars().collect())
         .collect()
 }
+
+// Busca la primera celda que contiene `target` y devuelve (columna, fila)
+pub fn find_cell(maze: &[Vec<char>], target: char) -> Option<(usize, usize)> {
+    maze.iter().enumerate().find_map(|(row_idx, row)| {
+        row.iter().position(|&cell| cell == target).map(|col_idx| (col_idx, row_idx))
+    })
+}
+
+// Genera un laberinto perfecto de `cols` x `rows` celdas con el mismo formato
+// que `maze.py`: 'p' arriba a la izquierda, 'g' abajo a la derecha,
+// `carrots` zanahorias 'z' y `hearts` corazones 'h' en celdas al azar
+pub fn generate_maze<R: Rng>(cols: usize, rows: usize, carrots: usize, hearts: usize, rng: &mut R) -> Vec<Vec<char>> {
+    let mut hor = vec![vec![true; cols]; rows + 1]; // Pared encima de cada celda
+    let mut ver = vec![vec![true; cols + 1]; rows]; // Pared a la izquierda de cada celda
+    let mut visited = vec![vec![false; cols]; rows];
+
+    // Recorrido en profundidad con pila explícita
+    let start = (rng.gen_range(0..cols), rng.gen_range(0..rows));
+    visited[start.1][start.0] = true;
+    let mut stack = vec![start];
+    while let Some(&(x, y)) = stack.last() {
+        let mut neighbours = Vec::with_capacity(4);
+        if x > 0 && !visited[y][x - 1] {
+            neighbours.push((x - 1, y));
+        }
+        if x + 1 < cols && !visited[y][x + 1] {
+            neighbours.push((x + 1, y));
+        }
+        if y > 0 && !visited[y - 1][x] {
+            neighbours.push((x, y - 1));
+        }
+        if y + 1 < rows && !visited[y + 1][x] {
+            neighbours.push((x, y + 1));
+        }
+
+        let Some(&(nx, ny)) = neighbours.choose(rng) else {
+            stack.pop();
+            continue;
+        };
+        if nx == x {
+            hor[y.max(ny)][x] = false;
+        } else {
+            ver[y][x.max(nx)] = false;
+        }
+        visited[ny][nx] = true;
+        stack.push((nx, ny));
+    }
+
+    let mut maze = Vec::with_capacity(rows * 2 + 1);
+    for (y, top) in hor.iter().enumerate() {
+        let mut line = String::new();
+        for &wall in top {
+            line.push_str(if wall { "+--" } else { "+  " });
+        }
+        line.push('+');
+        maze.push(line.chars().collect::<Vec<char>>());
+
+        if let Some(left) = ver.get(y) {
+            let mut line = String::new();
+            for (x, &wall) in left.iter().enumerate() {
+                line.push_str(if wall { "|" } else { " " });
+                if x < cols {
+                    line.push_str("  ");
+                }
+            }
+            maze.push(line.chars().collect());
+        }
+    }
+
+    maze[1][1] = 'p';
+    maze[rows * 2 - 1][cols * 3 - 1] = 'g';
+
+    // Zanahorias y corazones en celdas libres distintas del inicio y la meta
+    let mut free: Vec<(usize, usize)> = (0..rows)
+        .flat_map(|y| (0..cols).map(move |x| (x, y)))
+        .filter(|&(x, y)| (x, y) != (0, 0) && (x, y) != (cols - 1, rows - 1))
+        .collect();
+    free.shuffle(rng);
+    for (i, &(x, y)) in free.iter().take(carrots + hearts).enumerate() {
+        maze[y * 2 + 1][x * 3 + 1] = if i < carrots { 'z' } else { 'h' };
+    }
+
+    maze
+}
+
+// Suelo de las celdas por las que se anda: cambia el sonido de los pasos y el
+// color del mapa. En los archivos, ' ' es tierra, '.' piedra y ',' hierba
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Floor {
+    Dirt,
+    Stone,
+    Grass,
+}
+
+impl Floor {
+    pub fn from_tile(tile: char) -> Option<Self> {
+        match tile {
+            ' ' => Some(Floor::Dirt),
+            '.' => Some(Floor::Stone),
+            ',' => Some(Floor::Grass),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Floor::Dirt => "tierra",
+            Floor::Stone => "piedra",
+            Floor::Grass => "hierba",
+        }
+    }
+
+    pub fn rgba(self) -> [u8; 4] {
+        match self {
+            Floor::Dirt => [0xFF, 0xD7, 0xB3, 0xFF],
+            Floor::Stone => [0xC9, 0xC4, 0xBA, 0xFF],
+            Floor::Grass => [0xB5, 0xDE, 0x8E, 0xFF],
+        }
+    }
+}
+
+pub fn is_floor(tile: char) -> bool {
+    Floor::from_tile(tile).is_some()
+}
+
+// Qué es cada carácter del laberinto, para las herramientas de depuración
+pub fn tile_name(tile: char) -> &'static str {
+    match tile {
+        '+' => "esquina",
+        '-' | '|' => "pared",
+        'p' => "salida",
+        'g' => "meta",
+        'z' => "zanahoria",
+        'h' => "corazón",
+        'd' => "puerta",
+        tile if KeyColor::from_key_tile(tile).is_some() => "llave",
+        tile if KeyColor::from_door_tile(tile).is_some() => "puerta con llave",
+        tile => Floor::from_tile(tile).map_or("desconocido", Floor::name),
+    }
+}
+
+fn is_wall(tile: char) -> bool {
+    matches!(tile, '+' | '-' | '|')
+}
+
+// Celdas alcanzables desde 'p' sin cruzar paredes ni `blocked`; guarda de
+// dónde se llegó a cada una para poder rehacer el camino
+fn flood(maze: &[Vec<char>], blocked: impl Fn(char) -> bool) -> HashMap<(usize, usize), (usize, usize)> {
+    let mut prev = HashMap::new();
+    let Some(start) = find_cell(maze, 'p') else { return prev };
+    prev.insert(start, start);
+    let mut queue = VecDeque::from([start]);
+    while let Some((col, row)) = queue.pop_front() {
+        for (c, r) in [(col + 1, row), (col.wrapping_sub(1), row), (col, row + 1), (col, row.wrapping_sub(1))] {
+            let Some(&tile) = maze.get(r).and_then(|line| line.get(c)) else { continue };
+            if is_wall(tile) || blocked(tile) || prev.contains_key(&(c, r)) {
+                continue;
+            }
+            prev.insert((c, r), (col, row));
+            queue.push_back((c, r));
+        }
+    }
+    prev
+}
+
+// Cierra con una puerta de `color` un paso del camino entre la salida y la
+// meta, y deja la llave en una celda a la que se llega sin cruzarla. Devuelve
+// `false` si el laberinto no deja sitio
+pub fn add_locked_door<R: Rng>(maze: &mut [Vec<char>], color: KeyColor, rng: &mut R) -> bool {
+    let prev = flood(maze, |_| false);
+    let Some(goal) = find_cell(maze, 'g') else { return false };
+    if !prev.contains_key(&goal) {
+        return false;
+    }
+    let mut path = vec![goal];
+    let mut cell = goal;
+    while prev[&cell] != cell {
+        cell = prev[&cell];
+        path.push(cell);
+    }
+    path.reverse();
+
+    // Huecos en las líneas de pared, lejos de la salida y de la meta
+    let openings: Vec<(usize, usize)> =
+        path.iter().copied().filter(|&(col, row)| row % 2 == 0 || col % 3 == 0).collect();
+    let Some(&(col, row)) = openings.get(openings.len() / 4..openings.len() * 3 / 4).and_then(|o| o.choose(rng)) else {
+        return false;
+    };
+    let mut door = vec![(col, row)];
+    if row % 2 == 0 {
+        // Los huecos de las paredes horizontales tienen dos caracteres
+        door.push(if col % 3 == 1 { (col + 1, row) } else { (col - 1, row) });
+    }
+    for &(c, r) in &door {
+        maze[r][c] = color.door_tile();
+    }
+
+    let reachable = flood(maze, is_door_tile);
+    let mut spots: Vec<(usize, usize)> = reachable
+        .keys()
+        .copied()
+        .filter(|&(c, r)| r % 2 == 1 && c % 3 == 1 && is_floor(maze[r][c]))
+        .collect();
+    spots.sort_unstable(); // El orden del HashMap no es reproducible
+    match spots.choose(rng) {
+        Some(&(c, r)) => {
+            maze[r][c] = color.key_tile();
+            true
+        }
+        None => {
+            for &(c, r) in &door {
+                maze[r][c] = ' ';
+            }
+            false
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use nalgebra_glm::Vec2;
+
+use crate::door::{is_door_tile, KeyColor};
+use crate::framebuffer::Framebuffer;
+use crate::maze::Floor;
+use crate::render::{has_line_of_sight, HEART_COLOR};
+use crate::world::World;
+
+// Cómo se muestra el minimapa
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MinimapMode {
+    Rotating, // Gira con el jugador: arriba es hacia donde mira
+    Fixed,    // Norte arriba
+    Hidden,
+}
+
+pub const MINIMAP_MODES: [MinimapMode; 3] = [MinimapMode::Rotating, MinimapMode::Fixed, MinimapMode::Hidden];
+
+// Bloques que se ven desde el centro hasta el borde en cada nivel de zoom
+pub const MINIMAP_ZOOMS: [f32; 3] = [3.0, 6.0, 12.0];
+
+const FOG_COLOR: [u8; 4] = [0x10, 0x10, 0x18, 0xFF];
+const WALL_COLOR: [u8; 4] = [0x50, 0x50, 0x5A, 0xFF];
+const BORDER_COLOR: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+pub const PLAYER_COLOR: [u8; 4] = [0x00, 0xFF, 0x00, 0xFF];
+pub const CAT_COLOR: [u8; 4] = [0xFF, 0x20, 0x20, 0xFF];
+pub const CARROT_COLOR: [u8; 4] = [0xFF, 0x8C, 0x00, 0xFF];
+// Largo del cono de visión, en proporción al lado del minimapa
+const CONE_LENGTH: f32 = 0.35;
+
+impl MinimapMode {
+    // Nombre en `settings.cfg`
+    pub fn name(self) -> &'static str {
+        match self {
+            MinimapMode::Rotating => "rotating",
+            MinimapMode::Fixed => "fixed",
+            MinimapMode::Hidden => "hidden",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        MINIMAP_MODES.into_iter().find(|m| m.name() == name)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MinimapMode::Rotating => "Gira",
+            MinimapMode::Fixed => "Fijo",
+            MinimapMode::Hidden => "Oculto",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = MINIMAP_MODES.iter().position(|&m| m == self).unwrap_or(0);
+        MINIMAP_MODES[(i + 1) % MINIMAP_MODES.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let i = MINIMAP_MODES.iter().position(|&m| m == self).unwrap_or(0);
+        MINIMAP_MODES[(i + MINIMAP_MODES.len() - 1) % MINIMAP_MODES.len()]
+    }
+}
+
+pub fn zoom_label(zoom: usize) -> &'static str {
+    match zoom {
+        0 => "Cerca",
+        1 => "Medio",
+        _ => "Lejos",
+    }
+}
+
+// Paso entre el mundo y el minimapa: centrado en el jugador y, si gira, con
+// la dirección en la que mira hacia arriba
+struct MapTransform {
+    center: Vec2,  // Centro del minimapa en el framebuffer
+    origin: Vec2,  // Posición del jugador en el mundo
+    scale: f32,    // Píxeles del minimapa por píxel del mundo
+    right: Vec2,   // Dirección del mundo que queda a la derecha en el minimapa
+    up: Vec2,      // Dirección del mundo que queda arriba
+}
+
+impl MapTransform {
+    fn to_world(&self, screen: Vec2) -> Vec2 {
+        let offset = (screen - self.center) / self.scale;
+        self.origin + self.right * offset.x - self.up * offset.y
+    }
+
+    fn to_screen(&self, world: Vec2) -> Vec2 {
+        let offset = world - self.origin;
+        self.center + Vec2::new(offset.dot(&self.right), -offset.dot(&self.up)) * self.scale
+    }
+}
+
+// Minimapa de `size` píxeles en la esquina inferior derecha. Solo muestra las
+// celdas que el jugador ya ha visto, los objetos que hay en ellas y los gatos
+// que tiene a la vista
+pub fn render_minimap(framebuffer: &mut Framebuffer, world: &World, mode: MinimapMode, zoom: usize, size: usize) {
+    if mode == MinimapMode::Hidden || size == 0 {
+        return;
+    }
+    let bs = world.block_size as f32;
+    let player = &world.player;
+    let left = framebuffer.get_width().saturating_sub(size) as isize;
+    let top = framebuffer.get_height().saturating_sub(size) as isize;
+    let half = size as f32 / 2.0;
+    let zoom_blocks = MINIMAP_ZOOMS[zoom.min(MINIMAP_ZOOMS.len() - 1)];
+    let (right, up) = match mode {
+        MinimapMode::Rotating => (Vec2::new(-player.a.sin(), player.a.cos()), Vec2::new(player.a.cos(), player.a.sin())),
+        _ => (Vec2::new(1.0, 0.0), Vec2::new(0.0, -1.0)),
+    };
+    let map = MapTransform {
+        center: Vec2::new(left as f32 + half, top as f32 + half),
+        origin: player.pos,
+        scale: half / (zoom_blocks * bs),
+        right,
+        up,
+    };
+
+    let heading = Vec2::new(player.a.cos(), player.a.sin());
+    let cone_length = CONE_LENGTH * size as f32 / map.scale;
+    let cone_cos = (player.fov / 2.0).cos();
+    for y in top..top + size as isize {
+        for x in left..left + size as isize {
+            let pos = map.to_world(Vec2::new(x as f32 + 0.5, y as f32 + 0.5));
+            let mut color = cell_color(world, pos);
+            let to_pixel = pos - player.pos;
+            let distance = to_pixel.magnitude();
+            if distance < cone_length && distance > 0.0 && to_pixel.dot(&heading) / distance >= cone_cos {
+                color = mix(color, [0xFF, 0xFF, 0xA0, 0xFF], 0.3 * (1.0 - distance / cone_length));
+            }
+            framebuffer.set_pixel(x, y, color);
+        }
+    }
+
+    // Objetos en las celdas ya vistas
+    let dot = (size as f32 / 60.0).max(1.0) as isize;
+    for (row, line) in world.maze.iter().enumerate() {
+        for (col, &tile) in line.iter().enumerate() {
+            if !world.explored[row][col] {
+                continue;
+            }
+            let Some(color) = pickup_color(tile) else { continue };
+            let center = Vec2::new((col as f32 + 0.5) * bs, (row as f32 + 0.5) * bs);
+            draw_marker(framebuffer, &map, center, dot, color, (left, top, size));
+        }
+    }
+    for cat in &world.cats {
+        if has_line_of_sight(&player.pos, &cat.pos, &world.maze, world.block_size) {
+            draw_marker(framebuffer, &map, cat.pos, dot + 1, CAT_COLOR, (left, top, size));
+        }
+    }
+
+    let center = (map.center.x as isize, map.center.y as isize);
+    let tip = map.to_screen(player.pos + heading * bs * 0.6);
+    framebuffer.draw_line(center.0, center.1, tip.x as isize, tip.y as isize, PLAYER_COLOR);
+    framebuffer.fill_circle(center.0, center.1, dot + 1, PLAYER_COLOR);
+    framebuffer.draw_rect_outline(left, top, size, size, BORDER_COLOR);
+}
+
+// Color de lo que hay en `pos`, o niebla si todavía no se ha visto
+fn cell_color(world: &World, pos: Vec2) -> [u8; 4] {
+    if pos.x < 0.0 || pos.y < 0.0 {
+        return FOG_COLOR;
+    }
+    let (col, row) = ((pos.x / world.block_size as f32) as usize, (pos.y / world.block_size as f32) as usize);
+    match world.explored.get(row).and_then(|line| line.get(col)) {
+        Some(true) => tile_color(world.maze[row][col]),
+        _ => FOG_COLOR,
+    }
+}
+
+// Color de una celda en los mapas
+pub fn tile_color(tile: char) -> [u8; 4] {
+    match tile {
+        'p' => [0x00, 0xA0, 0x00, 0xFF],
+        'g' => [0xFF, 0x00, 0x00, 0xFF],
+        tile if is_door_tile(tile) => match KeyColor::from_door_tile(tile) {
+            Some(lock) => lock.rgba(),
+            None => [0x8B, 0x5A, 0x2B, 0xFF],
+        },
+        // Los objetos se pintan encima como puntos sobre el suelo
+        tile if pickup_color(tile).is_some() => Floor::Dirt.rgba(),
+        tile => Floor::from_tile(tile).map_or(WALL_COLOR, Floor::rgba),
+    }
+}
+
+// Color del punto con el que se marca un objeto que se puede recoger
+pub fn pickup_color(tile: char) -> Option<[u8; 4]> {
+    match tile {
+        'z' => Some(CARROT_COLOR),
+        'h' => Some(HEART_COLOR),
+        tile => KeyColor::from_key_tile(tile).map(KeyColor::rgba),
+    }
+}
+
+fn draw_marker(
+    framebuffer: &mut Framebuffer,
+    map: &MapTransform,
+    world_pos: Vec2,
+    radius: isize,
+    color: [u8; 4],
+    (left, top, size): (isize, isize, usize),
+) {
+    let p = map.to_screen(world_pos);
+    let (x, y) = (p.x as isize, p.y as isize);
+    let inside = |v: isize, start: isize| v - radius >= start && v + radius < start + size as isize;
+    if inside(x, left) && inside(y, top) {
+        framebuffer.fill_circle(x, y, radius, color);
+    }
+}
+
+fn mix(a: [u8; 4], b: [u8; 4], t: f32) -> [u8; 4] {
+    let channel = |i: usize| (a[i] as f32 + (b[i] as f32 - a[i] as f32) * t) as u8;
+    [channel(0), channel(1), channel(2), 0xFF]
+}
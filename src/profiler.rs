@@ -0,0 +1,116 @@
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+
+// Partes de un fotograma que se miden por separado
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Clear,
+    Walls,
+    Floor,
+    Sprites,
+    Minimap,
+    Hud,
+    Present,
+}
+
+pub const PHASES: [Phase; 7] =
+    [Phase::Clear, Phase::Walls, Phase::Floor, Phase::Sprites, Phase::Minimap, Phase::Hud, Phase::Present];
+
+// Fotogramas que se guardan para la gráfica
+pub const FRAME_HISTORY: usize = 120;
+
+impl Phase {
+    pub fn label(self) -> &'static str {
+        match self {
+            Phase::Clear => "limpiar",
+            Phase::Walls => "paredes",
+            Phase::Floor => "cielo y suelo",
+            Phase::Sprites => "sprites",
+            Phase::Minimap => "mapas",
+            Phase::Hud => "HUD",
+            Phase::Present => "presentar",
+        }
+    }
+
+    fn index(self) -> usize {
+        PHASES.iter().position(|&p| p == self).unwrap_or(0)
+    }
+}
+
+// Tiempos del último fotograma terminado, en milisegundos
+#[derive(Clone, Debug, Default)]
+pub struct FrameStats {
+    pub phases: [f32; PHASES.len()],
+    pub frame_times: VecDeque<f32>, // Los últimos `FRAME_HISTORY`, el más reciente al final
+    pub rays: u64,
+    pub ray_steps: u64,
+}
+
+impl FrameStats {
+    pub fn average_ray_steps(&self) -> f32 {
+        if self.rays == 0 {
+            0.0
+        } else {
+            self.ray_steps as f32 / self.rays as f32
+        }
+    }
+}
+
+#[derive(Default)]
+struct Profiler {
+    current: [f32; PHASES.len()], // Lo que va sumando el fotograma en curso
+    rays: u64,
+    ray_steps: u64,
+    last: FrameStats,
+}
+
+static PROFILER: Lazy<Mutex<Profiler>> = Lazy::new(|| Mutex::new(Profiler::default()));
+
+// Cuenta el tiempo de `phase` hasta que se suelta
+pub struct PhaseTimer {
+    phase: Phase,
+    start: Instant,
+}
+
+impl Drop for PhaseTimer {
+    fn drop(&mut self) {
+        let ms = self.start.elapsed().as_secs_f32() * 1000.0;
+        PROFILER.lock().unwrap().current[self.phase.index()] += ms;
+    }
+}
+
+// `let _timer = time(Phase::Walls);` mide hasta el final del bloque. Una fase
+// que se mide varias veces en el mismo fotograma suma los tiempos
+pub fn time(phase: Phase) -> PhaseTimer {
+    PhaseTimer { phase, start: Instant::now() }
+}
+
+// Rayos de las columnas del render 3D y los pasos que dieron entre todos. Los
+// demás rayos (mapa, sonido, lo que se ve) no cuentan
+pub fn count_rays(rays: u64, steps: u64) {
+    let mut profiler = PROFILER.lock().unwrap();
+    profiler.rays += rays;
+    profiler.ray_steps += steps;
+}
+
+// Cierra el fotograma que tardó `frame_ms` y deja sus tiempos para `last_frame`
+pub fn end_frame(frame_ms: f32) {
+    let mut profiler = PROFILER.lock().unwrap();
+    let phases = std::mem::take(&mut profiler.current);
+    let rays = std::mem::take(&mut profiler.rays);
+    let ray_steps = std::mem::take(&mut profiler.ray_steps);
+    let last = &mut profiler.last;
+    last.phases = phases;
+    last.rays = rays;
+    last.ray_steps = ray_steps;
+    last.frame_times.push_back(frame_ms);
+    if last.frame_times.len() > FRAME_HISTORY {
+        last.frame_times.pop_front();
+    }
+}
+
+pub fn last_frame() -> FrameStats {
+    PROFILER.lock().unwrap().last.clone()
+}
@@ -0,0 +1,473 @@
+use image::{DynamicImage, GenericImageView, ImageReader};
+use nalgebra_glm::Vec2;
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use crate::cast_ray::{trace_ray, Intersect};
+use crate::door::{Door, KeyColor};
+use crate::framebuffer::Framebuffer;
+use crate::maze::{is_floor, Floor};
+use crate::player::Player;
+use crate::profiler::{self, Phase};
+use crate::scaling::fit_rect;
+use crate::texture::Texture;
+
+// Texturas de las paredes
+pub static WALL1: Lazy<Arc<Texture>> = Lazy::new(|| Arc::new(Texture::new("assets/cerca3a.png")));
+pub static WALL2: Lazy<Arc<Texture>> = Lazy::new(|| Arc::new(Texture::new("assets/puerta3.png")));
+pub static CARROT: Lazy<Arc<Texture>> = Lazy::new(|| Arc::new(Texture::new("assets/Z3.png")));
+pub static CAT_TEXTURE: Lazy<Arc<Texture>> = Lazy::new(|| Arc::new(Texture::new("assets/sprite/gatoM.png")));
+
+// Pantallas completas: inicio, victoria y derrota
+pub static INTRO_SCREEN: Lazy<DynamicImage> = Lazy::new(|| load_screen("assets/inicio.gif"));
+pub static WIN_SCREEN: Lazy<DynamicImage> = Lazy::new(|| load_screen("assets/Fin.png"));
+pub static LOSE_SCREEN: Lazy<DynamicImage> = Lazy::new(|| load_screen("assets/sprite/Perdio.png"));
+
+fn load_screen(file_path: &str) -> DynamicImage {
+    ImageReader::open(file_path).unwrap().decode().unwrap()
+}
+
+// Dibuja la imagen ajustada al framebuffer sin deformarla
+pub fn render_image(framebuffer: &mut Framebuffer, img: &image::DynamicImage) {
+    let (img_width, img_height) = img.dimensions();
+    let (off_x, off_y, out_w, out_h) =
+        fit_rect(img_width as usize, img_height as usize, framebuffer.get_width(), framebuffer.get_height());
+    for y in 0..out_h {
+        for x in 0..out_w {
+            let src_x = (x * img_width as usize / out_w) as u32;
+            let src_y = (y * img_height as usize / out_h) as u32;
+            let pixel = img.get_pixel(src_x, src_y);
+            framebuffer.point(off_x + x, off_y + y, [pixel[0], pixel[1], pixel[2], pixel[3]]);
+        }
+    }
+}
+
+// Bloques que caben de arriba abajo en la vista 2D, y sus límites de zoom
+pub const VIEW2D_BLOCKS: f32 = 12.0;
+pub const VIEW2D_BLOCKS_MIN: f32 = 4.0;
+pub const VIEW2D_BLOCKS_MAX: f32 = 40.0;
+// Una de cada tantas columnas de pantalla lanza un rayo en la vista 2D
+const VIEW2D_RAY_SPACING: usize = 4;
+const RAY_COLOR: [u8; 4] = [0xFF, 0xDD, 0xDD, 0xFF];
+const OUTSIDE_COLOR: [u8; 4] = [0x00, 0x00, 0x00, 0xFF];
+
+// Cámara de la vista 2D: qué punto del mundo queda en el centro de la pantalla
+// y cuántos píxeles de pantalla ocupa cada píxel del mundo
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera2d {
+    pub center: Vec2,
+    pub scale: f32,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Camera2d {
+    // Centrada en `target` con `blocks` bloques de alto, sin enseñar lo que hay
+    // fuera del laberinto salvo si este es más pequeño que la pantalla
+    pub fn follow(target: Vec2, maze: &[Vec<char>], block_size: usize, blocks: f32, width: usize, height: usize) -> Self {
+        let scale = height as f32 / (blocks * block_size as f32);
+        let half = Vec2::new(width as f32, height as f32) / (2.0 * scale);
+        let size = Vec2::new(maze[0].len() as f32, maze.len() as f32) * block_size as f32;
+        let clamp = |value: f32, half: f32, size: f32| {
+            if size <= half * 2.0 {
+                size / 2.0
+            } else {
+                value.clamp(half, size - half)
+            }
+        };
+        let center = Vec2::new(clamp(target.x, half.x, size.x), clamp(target.y, half.y, size.y));
+        Self { center, scale, width, height }
+    }
+
+    pub fn to_screen(&self, world: Vec2) -> Vec2 {
+        (world - self.center) * self.scale + Vec2::new(self.width as f32, self.height as f32) / 2.0
+    }
+
+    pub fn to_world(&self, screen: Vec2) -> Vec2 {
+        (screen - Vec2::new(self.width as f32, self.height as f32) / 2.0) / self.scale + self.center
+    }
+}
+
+pub fn render2d(
+    framebuffer: &mut Framebuffer,
+    player: &Player,
+    maze: &[Vec<char>],
+    doors: &[Door],
+    camera: &Camera2d,
+    block_size: usize,
+) {
+    let _timer = profiler::time(Phase::Walls);
+    let bs = block_size as f32;
+    let (width, height) = (framebuffer.get_width(), framebuffer.get_height());
+    // Cada fila de pantalla se pinta en un hilo, como en `render3d`
+    let pool = Arc::clone(&RENDER_POOL.read().unwrap());
+    pool.install(|| {
+        framebuffer
+            .buffer
+            .par_chunks_mut(width * 4)
+            .take(height)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for x in 0..width {
+                    let pos = camera.to_world(Vec2::new(x as f32 + 0.5, y as f32 + 0.5));
+                    let (col, row_idx) = ((pos.x / bs).floor(), (pos.y / bs).floor());
+                    let tile = (col >= 0.0 && row_idx >= 0.0)
+                        .then(|| maze.get(row_idx as usize).and_then(|line| line.get(col as usize)))
+                        .flatten();
+                    let color = match tile {
+                        Some(&tile) => tile_texel(tile, pos.x / bs - col, pos.y / bs - row_idx),
+                        None => OUTSIDE_COLOR,
+                    };
+                    row[x * 4..x * 4 + 4].copy_from_slice(&color);
+                }
+            });
+    });
+
+    let origin = camera.to_screen(player.pos);
+    let num_rays = width / VIEW2D_RAY_SPACING;
+    for i in 0..num_rays {
+        let current_ray = i as f32 / num_rays as f32;
+        let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
+        let intersect = trace_ray(maze, doors, player, a, block_size);
+        let end = camera.to_screen(player.pos + Vec2::new(a.cos(), a.sin()) * intersect.distance);
+        framebuffer.draw_line(origin.x as isize, origin.y as isize, end.x as isize, end.y as isize, RAY_COLOR);
+    }
+    let radius = (bs * camera.scale / 6.0).max(2.0) as isize;
+    framebuffer.fill_circle(origin.x as isize, origin.y as isize, radius, [0x00, 0xFF, 0x00, 0xFF]);
+}
+
+pub const HIGHLIGHT_COLOR: [u8; 4] = [0xFF, 0xD7, 0x00, 0xFF];
+
+// Rayo de ángulo `a` resaltado sobre la vista 2D, con una marca donde choca
+pub fn highlight_ray(
+    framebuffer: &mut Framebuffer,
+    camera: &Camera2d,
+    player: &Player,
+    maze: &[Vec<char>],
+    doors: &[Door],
+    a: f32,
+    block_size: usize,
+) -> Intersect {
+    let intersect = trace_ray(maze, doors, player, a, block_size);
+    let origin = camera.to_screen(player.pos);
+    let end = camera.to_screen(player.pos + Vec2::new(a.cos(), a.sin()) * intersect.distance);
+    // Repetida un píxel a cada lado para que destaque sobre el abanico
+    for offset in [-1.0, 0.0, 1.0] {
+        framebuffer.draw_line_aa(origin.x + offset, origin.y, end.x + offset, end.y, HIGHLIGHT_COLOR);
+        framebuffer.draw_line_aa(origin.x, origin.y + offset, end.x, end.y + offset, HIGHLIGHT_COLOR);
+    }
+    framebuffer.draw_circle(end.x as isize, end.y as isize, 6, HIGHLIGHT_COLOR);
+    intersect
+}
+
+// Color de la celda `tile` en el punto (`u`, `v`) de su interior, de 0 a 1
+fn tile_texel(tile: char, u: f32, v: f32) -> [u8; 4] {
+    let sample = |texture: &Texture| {
+        let tx = ((u * texture.width as f32) as u32).min(texture.width - 1);
+        let ty = ((v * texture.height as f32) as u32).min(texture.height - 1);
+        texture.get_pixel_color(tx, ty)
+    };
+    match tile {
+        '+' | '-' | '|' => sample(&WALL1),
+        'g' | 'd' => sample(&WALL2),
+        'z' => sample(&CARROT),
+        'h' => {
+            if heart_shape(u, v) {
+                HEART_COLOR
+            } else {
+                HEART_BACKGROUND
+            }
+        }
+        'p' => [0x00, 0xFF, 0x00, 0xFF],
+        tile => match (Floor::from_tile(tile), KeyColor::from_key_tile(tile), KeyColor::from_door_tile(tile)) {
+            (Some(floor), _, _) => floor_texel(floor, u, v),
+            (_, Some(key), _) => key_color(key, u, v),
+            (_, _, Some(lock)) => tint(sample(&WALL2), lock.rgba()),
+            _ => OUTSIDE_COLOR,
+        },
+    }
+}
+
+// El color del suelo con manchas en una cuadrícula de 8x8 por celda para que
+// no quede liso
+fn floor_texel(floor: Floor, u: f32, v: f32) -> [u8; 4] {
+    let (i, j) = ((u * 8.0) as u32, (v * 8.0) as u32);
+    let hash = (i.wrapping_mul(73_856_093) ^ j.wrapping_mul(19_349_663)) % 16;
+    shade(floor.rgba(), 0.88 + hash as f32 * 0.01)
+}
+
+// Pool de hilos del render 3D y de la vista 2D; `set_render_threads` lo reemplaza
+static RENDER_POOL: Lazy<RwLock<Arc<ThreadPool>>> = Lazy::new(|| {
+    RwLock::new(Arc::new(build_render_pool(0).expect("No se pudo crear el pool de render")))
+});
+
+fn build_render_pool(threads: usize) -> Result<ThreadPool, ThreadPoolBuildError> {
+    ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|i| format!("render-{}", i))
+        .build()
+}
+
+// Cambia el número de hilos usados por `render3d` y `render2d` (0 = uno por núcleo).
+// La imagen resultante es la misma con cualquier número de hilos.
+pub fn set_render_threads(threads: usize) -> Result<(), ThreadPoolBuildError> {
+    let pool = build_render_pool(threads)?;
+    *RENDER_POOL.write().unwrap() = Arc::new(pool);
+    Ok(())
+}
+
+pub fn render_threads() -> usize {
+    RENDER_POOL.read().unwrap().current_num_threads()
+}
+
+// Franja de pared de una columna de pantalla
+struct Stake {
+    top: usize,
+    bottom: usize,
+    intersect: Intersect,
+}
+
+// La niebla es total a esta distancia, medida en bloques
+const FOG_DISTANCE_BLOCKS: f32 = 8.0;
+const FOG_COLOR: [u8; 4] = [163, 240, 255, 255]; // El mismo celeste del cielo
+
+// Mezcla el color de la pared con el de la niebla según su distancia
+fn apply_fog(color: [u8; 4], amount: f32) -> [u8; 4] {
+    let mut out = color;
+    for (channel, fog) in out.iter_mut().zip(FOG_COLOR).take(3) {
+        *channel = (*channel as f32 + (fog as f32 - *channel as f32) * amount) as u8;
+    }
+    out
+}
+
+// Corazón dibujado con su ecuación implícita; `u` y `v` van de 0 a 1 con el
+// origen arriba a la izquierda
+pub fn heart_shape(u: f32, v: f32) -> bool {
+    let x = (u - 0.5) * 2.6;
+    let y = (0.45 - v) * 2.6;
+    let a = x * x + y * y - 1.0;
+    a * a * a - x * x * y * y * y <= 0.0
+}
+
+pub const HEART_COLOR: [u8; 4] = [0xE0, 0x20, 0x40, 0xFF];
+const HEART_BACKGROUND: [u8; 4] = [0xFF, 0xD7, 0xB3, 0xFF];
+
+// Oscurece un color; `amount` = 1 lo deja igual
+fn shade(color: [u8; 4], amount: f32) -> [u8; 4] {
+    [(color[0] as f32 * amount) as u8, (color[1] as f32 * amount) as u8, (color[2] as f32 * amount) as u8, color[3]]
+}
+
+// Mezcla a partes iguales la textura de la puerta con el color de la llave
+fn tint(color: [u8; 4], with: [u8; 4]) -> [u8; 4] {
+    [
+        ((color[0] as u16 + with[0] as u16) / 2) as u8,
+        ((color[1] as u16 + with[1] as u16) / 2) as u8,
+        ((color[2] as u16 + with[2] as u16) / 2) as u8,
+        color[3],
+    ]
+}
+
+fn wall_color(intersect: &Intersect, ty: u32, block_size: usize) -> [u8; 4] {
+    let tx = intersect.tx as u32;
+    match intersect.impact {
+        '+' | '-' | '|' => WALL1.get_pixel_color(tx, ty),
+        'g' => WALL2.get_pixel_color(tx, ty),
+        'z' => CARROT.get_pixel_color(tx, ty), // Textura de zanahoria
+        'h' => {
+            if heart_shape(tx as f32 / block_size as f32, ty as f32 / 128.0) {
+                HEART_COLOR
+            } else {
+                HEART_BACKGROUND
+            }
+        }
+        'd' => WALL2.get_pixel_color(tx, ty),
+        tile => match (KeyColor::from_key_tile(tile), KeyColor::from_door_tile(tile)) {
+            (Some(key), _) => key_color(key, tx as f32 / block_size as f32, ty as f32 / 128.0),
+            (_, Some(lock)) => tint(WALL2.get_pixel_color(tx, ty), lock.rgba()),
+            _ => [0x00, 0x00, 0x00, 0xFF],
+        },
+    }
+}
+
+// Llave dibujada sobre el suelo del bloque: una anilla y una barra con dientes
+fn key_color(key: KeyColor, u: f32, v: f32) -> [u8; 4] {
+    if key_shape(u, v) {
+        key.rgba()
+    } else {
+        HEART_BACKGROUND
+    }
+}
+
+// Silueta de una llave; `u` y `v` van de 0 a 1 como en `heart_shape`
+pub fn key_shape(u: f32, v: f32) -> bool {
+    let (dx, dy) = (u - 0.28, v - 0.5);
+    let ring = dx * dx + dy * dy;
+    let bar = (0.28..0.88).contains(&u) && (0.44..0.56).contains(&v);
+    let teeth = ((0.62..0.7).contains(&u) || (0.76..0.84).contains(&u)) && (0.56..0.68).contains(&v);
+    (0.015..0.05).contains(&ring) || bar || teeth
+}
+
+pub fn render3d(
+    framebuffer: &mut Framebuffer,
+    player: &Player,
+    block_size: usize,
+    maze: &[Vec<char>],
+    doors: &[Door],
+    fog: bool,
+) {
+    let fog_distance = FOG_DISTANCE_BLOCKS * block_size as f32;
+    let num_rays = framebuffer.get_width();
+    let height = framebuffer.get_height();
+    let hw = framebuffer.get_width() as f32 / 2.0;
+    let hh = framebuffer.get_height() as f32 / 2.0;
+    let distance_to_projection_plane = hw / (player.fov / 2.0).tan();
+
+    {
+        let _timer = profiler::time(Phase::Floor);
+        framebuffer.draw_sky_and_ground();
+    }
+
+    let _timer = profiler::time(Phase::Walls);
+    let pool = Arc::clone(&RENDER_POOL.read().unwrap());
+    // Pasos de todos los rayos de este fotograma, para el panel de depuración
+    let steps = AtomicU64::new(0);
+    pool.install(|| {
+        // Primera pasada: un rayo por columna, repartidas entre los hilos.
+        // `collect` conserva el orden de las columnas.
+        let stakes: Vec<Option<Stake>> = (0..num_rays)
+            .into_par_iter()
+            .map(|i| {
+                let current_ray = i as f32 / num_rays as f32;
+                let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
+
+                let intersect = trace_ray(maze, doors, player, a, block_size);
+                steps.fetch_add(intersect.steps as u64, Ordering::Relaxed);
+                let distance_to_wall = intersect.distance;
+
+                if distance_to_wall < 0.01 {
+                    return None;
+                }
+
+                let stake_height = (distance_to_projection_plane / distance_to_wall) * block_size as f32;
+                let stake_top = (hh - (stake_height / 2.0)) as isize;
+                let stake_bottom = (hh + (stake_height / 2.0)) as isize;
+
+                let top = stake_top.max(0) as usize;
+                let bottom = stake_bottom.min(height as isize) as usize;
+
+                Some(Stake { top, bottom, intersect })
+            })
+            .collect();
+
+        // Segunda pasada: cada hilo pinta filas completas del framebuffer, que
+        // son trozos disjuntos del buffer, así que no hay escrituras compartidas
+        let row_bytes = num_rays * 4;
+        framebuffer
+            .buffer
+            .par_chunks_mut(row_bytes)
+            .take(height)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (i, stake) in stakes.iter().enumerate() {
+                    let Some(stake) = stake else { continue };
+                    if y < stake.top || y >= stake.bottom {
+                        continue;
+                    }
+
+                    let ty = ((y as f32 - stake.top as f32) / (stake.bottom as f32 - stake.top as f32) * 128.0) as u32;
+                    let mut color = wall_color(&stake.intersect, ty, block_size);
+                    if fog {
+                        color = apply_fog(color, (stake.intersect.distance / fog_distance).min(1.0));
+                    }
+                    row[i * 4..i * 4 + 4].copy_from_slice(&color);
+                }
+            });
+    });
+    profiler::count_rays(num_rays as u64, steps.into_inner());
+}
+
+pub fn check_cat_collision(cat_pos: Vec2, maze: &[Vec<char>], block_size: usize) -> bool {
+    let i = (cat_pos.x / block_size as f32) as usize;
+    let j = (cat_pos.y / block_size as f32) as usize;
+    
+    // Verifica si la celda actual es una pared
+    maze[j][i] == '+' || maze[j][i] == '-' || maze[j][i] == '|'
+}
+
+pub fn render_cat_sprite(
+    framebuffer: &mut Framebuffer,
+    player: &Player,
+    cat_pos: Vec2,  // Posición del gato
+    distance_to_projection_plane: f32,
+    block_size: usize,
+    maze: &[Vec<char>],  // Referencia al laberinto
+    max_distance: f32,  // Distancia máxima de visibilidad
+) {
+    // Verificar si el gato está colisionando con una pared
+    if check_cat_collision(cat_pos, maze, block_size) {
+        return;  // Si el gato está en una pared, no se renderiza
+    }
+
+    let direction = cat_pos - player.pos;
+    let distance = direction.magnitude();
+
+    // Evitar que el gato se vea si está más allá de la distancia máxima
+    if distance > max_distance {
+        return;
+    }
+
+    // Verificar si hay una pared entre el jugador y el gato (línea de visión)
+    if !has_line_of_sight(&player.pos, &cat_pos, maze, block_size) {
+        return;
+    }
+
+    let angle_to_player = direction.y.atan2(direction.x) - player.a;
+
+    let sprite_height = (distance_to_projection_plane / distance) * block_size as f32;
+    let sprite_top = (framebuffer.get_height() as f32 / 2.0 - sprite_height / 2.0).max(0.0) as usize;
+    let sprite_bottom = (framebuffer.get_height() as f32 / 2.0 + sprite_height / 2.0).min(framebuffer.get_height() as f32) as usize;
+
+    let sprite_width = sprite_height;  // Suponiendo que el sprite es cuadrado
+    let sprite_left = (framebuffer.get_width() as f32 / 2.0 + angle_to_player * distance_to_projection_plane).max(0.0) as usize;
+    let sprite_right = (sprite_left as f32 + sprite_width).min(framebuffer.get_width() as f32) as usize;
+
+    for y in sprite_top..sprite_bottom {
+        for x in sprite_left..sprite_right {
+            let tx = ((x - sprite_left) as f32 / (sprite_right - sprite_left) as f32 * CAT_TEXTURE.width as f32) as u32;
+            let ty = ((y - sprite_top) as f32 / (sprite_bottom - sprite_top) as f32 * CAT_TEXTURE.height as f32) as u32;
+            let color = CAT_TEXTURE.get_pixel_color(tx, ty);
+
+            framebuffer.point(x, y, color);
+        }
+    }
+}
+
+
+pub fn has_line_of_sight(start_pos: &Vec2, target_pos: &Vec2, maze: &[Vec<char>], block_size: usize) -> bool {
+    let delta = *target_pos - *start_pos;
+    let steps = delta.magnitude() as usize;
+    let step = delta / steps as f32;
+
+    // La celda del propio objetivo no tapa (una zanahoria no se tapa a sí misma)
+    let target_cell = ((target_pos.x / block_size as f32) as usize, (target_pos.y / block_size as f32) as usize);
+
+    let mut current_pos = *start_pos;
+    for _ in 0..steps {
+        current_pos += step;
+
+        // Verificar si la posición actual está dentro de una pared
+        let i = (current_pos.x / block_size as f32) as usize;
+        let j = (current_pos.y / block_size as f32) as usize;
+        if (i, j) == target_cell {
+            break;
+        }
+        if !is_floor(maze[j][i]) {
+            return false;  // Hay una pared bloqueando la línea de visión
+        }
+    }
+
+    true  // No hay obstrucciones en la línea de visión
+}
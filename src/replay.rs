@@ -0,0 +1,273 @@
+use nalgebra_glm::Vec2;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::game::Game;
+use crate::input::{Action, Input};
+use crate::settings::Settings;
+use crate::storage::{FirstReads, MemoryStorage};
+
+pub const REPLAY_VERSION: u32 = 2;
+const REPLAY_HEADER: &str = "raycaster-replay";
+
+// Estado que tiene que coincidir exactamente al terminar la repetición
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayCheck {
+    pub player_pos: Vec2,
+    pub player_a: f32,
+    pub score: u32,
+    pub health: u32,
+    pub lives: u32,
+    pub state: String, // `GameState` tal como lo escribe `{:?}`
+    pub cats: Vec<(Vec2, f32)>, // Posición y dirección
+}
+
+impl ReplayCheck {
+    pub fn of(game: &Game) -> Self {
+        let world = &game.world;
+        Self {
+            player_pos: world.player.pos,
+            player_a: world.player.a,
+            score: world.score,
+            health: world.health,
+            lives: world.lives,
+            state: format!("{:?}", game.state),
+            cats: world.cats.iter().map(|cat| (cat.pos, cat.direction)).collect(),
+        }
+    }
+
+    // Compara los bits de cada `f32`, no solo el valor
+    pub fn matches(&self, other: &ReplayCheck) -> bool {
+        let same = |a: f32, b: f32| a.to_bits() == b.to_bits();
+        same(self.player_pos.x, other.player_pos.x)
+            && same(self.player_pos.y, other.player_pos.y)
+            && same(self.player_a, other.player_a)
+            && self.score == other.score
+            && self.health == other.health
+            && self.lives == other.lives
+            && self.state == other.state
+            && self.cats.len() == other.cats.len()
+            && self.cats.iter().zip(&other.cats).all(|((p, d), (q, e))| {
+                same(p.x, q.x) && same(p.y, q.y) && same(*d, *e)
+            })
+    }
+}
+
+impl fmt::Display for ReplayCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "jugador: ({}, {}) ángulo {}", self.player_pos.x, self.player_pos.y, self.player_a)?;
+        writeln!(f, "puntos: {} · salud: {} · vidas: {}", self.score, self.health, self.lives)?;
+        writeln!(f, "estado: {}", self.state)?;
+        for (i, (pos, direction)) in self.cats.iter().enumerate() {
+            writeln!(f, "gato {}: ({}, {}) dirección {}", i, pos.x, pos.y, direction)?;
+        }
+        Ok(())
+    }
+}
+
+// Partida grabada: todo lo necesario para volver a jugarla igual
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub levels: Vec<String>,
+    pub fov_degrees: f32,
+    pub inputs: Vec<(u64, Input)>, // (tick, entrada), en orden
+    pub ticks: u64,                 // Duración total
+    pub files: FirstReads,          // Partidas y récords que leyó el juego, como estaban
+    pub check: Option<ReplayCheck>, // Estado al terminar la grabación
+}
+
+impl Replay {
+    pub fn new(seed: u64, levels: Vec<String>, fov_degrees: f32) -> Self {
+        Self { seed, levels, fov_degrees, inputs: Vec::new(), ticks: 0, files: Vec::new(), check: None }
+    }
+
+    // Vuelve a jugar la partida con el mismo paso fijo; `on_tick` se llama
+    // después de cada tick
+    pub fn play(&self, mut on_tick: impl FnMut(&Game)) -> Game {
+        let settings = Settings { fov_degrees: self.fov_degrees, ..Settings::default() };
+        let mut game = Game::new(self.levels.clone(), self.seed, settings);
+        // Los archivos en memoria: la repetición no ve ni cambia los del jugador
+        let files = self.files.iter().filter_map(|(key, text)| Some((key.clone(), text.clone()?))).collect();
+        game.set_storage(Box::new(MemoryStorage { files }));
+
+        let mut inputs = self.inputs.iter().peekable();
+        for tick in 0..self.ticks {
+            while let Some((_, input)) = inputs.next_if(|(t, _)| *t == tick) {
+                game.apply(*input);
+            }
+            game.tick();
+            on_tick(&game);
+        }
+        // Entradas que llegaron después del último tick
+        for (_, input) in inputs {
+            game.apply(*input);
+        }
+        game
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        Self::parse(&text)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    // Formato de texto como el de las partidas guardadas; cada entrada es
+    // `input = <tick> key <acción> down|up`, `input = <tick> look <radianes>`
+    // o `input = <tick> text <código del carácter>`, y cada archivo leído
+    // `file = <clave> <contenido>` con los saltos de línea escapados
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{} {}\n", REPLAY_HEADER, REPLAY_VERSION);
+        text += &format!("seed = {}\n", self.seed);
+        for level in &self.levels {
+            text += &format!("level = {}\n", level);
+        }
+        text += &format!("fov = {}\n", self.fov_degrees);
+        for (key, contents) in &self.files {
+            if let Some(contents) = contents {
+                text += &format!("file = {} {}\n", key, escape(contents));
+            }
+        }
+        for (tick, input) in &self.inputs {
+            match input {
+                Input::Key { action, pressed } => {
+                    text += &format!("input = {} key {} {}\n", tick, action.name(), if *pressed { "down" } else { "up" })
+                }
+                Input::Look(delta) => text += &format!("input = {} look {}\n", tick, delta),
+                Input::Text(c) => text += &format!("input = {} text {}\n", tick, *c as u32),
+            }
+        }
+        text += &format!("ticks = {}\n", self.ticks);
+        if let Some(check) = &self.check {
+            text += &format!("check_player = {} {} {}\n", check.player_pos.x, check.player_pos.y, check.player_a);
+            text += &format!("check_score = {}\n", check.score);
+            text += &format!("check_health = {}\n", check.health);
+            text += &format!("check_lives = {}\n", check.lives);
+            text += &format!("check_state = {}\n", check.state);
+            for (pos, direction) in &check.cats {
+                text += &format!("check_cat = {} {} {}\n", pos.x, pos.y, direction);
+            }
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate();
+        let version = lines
+            .next()
+            .and_then(|(_, header)| header.strip_prefix(REPLAY_HEADER))
+            .and_then(|version| version.trim().parse::<u32>().ok())
+            .ok_or("el archivo no es una repetición")?;
+        if version != REPLAY_VERSION {
+            return Err(format!("repetición con formato {}, este lee el {}", version, REPLAY_VERSION));
+        }
+
+        let mut replay = Replay::new(0, Vec::new(), Settings::default().fov_degrees);
+        let mut check_player = None;
+        let mut check_score = None;
+        let mut check_health = None;
+        let mut check_lives = None;
+        let mut check_state = None;
+        let mut check_cats = Vec::new();
+
+        for (idx, line) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |reason: &str| format!("línea {}: {}", idx + 1, reason);
+            let (key, value) = line.split_once('=').ok_or_else(|| err("se esperaba `clave = valor`"))?;
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "seed" => replay.seed = value.parse().map_err(|_| err("semilla no válida"))?,
+                "level" => replay.levels.push(value.to_string()),
+                "fov" => replay.fov_degrees = value.parse().map_err(|_| err("fov no válido"))?,
+                "ticks" => replay.ticks = value.parse().map_err(|_| err("ticks no válidos"))?,
+                "input" => {
+                    let input = parse_input(value).ok_or_else(|| err("entrada no válida"))?;
+                    if replay.inputs.last().is_some_and(|(last, _)| *last > input.0) {
+                        return Err(err("las entradas no están en orden"));
+                    }
+                    replay.inputs.push(input);
+                }
+                "check_player" => {
+                    let v: Vec<f32> = value.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+                    let [x, y, a] = v[..] else { return Err(err("se esperaban x y ángulo")) };
+                    check_player = Some((Vec2::new(x, y), a));
+                }
+                "file" => {
+                    let (key, contents) = value.split_once(' ').unwrap_or((value, ""));
+                    let contents = unescape(contents).ok_or_else(|| err("contenido de archivo no válido"))?;
+                    replay.files.push((key.to_string(), Some(contents)));
+                }
+                "check_score" => check_score = Some(value.parse().map_err(|_| err("puntos no válidos"))?),
+                "check_health" => check_health = Some(value.parse().map_err(|_| err("salud no válida"))?),
+                "check_lives" => check_lives = Some(value.parse().map_err(|_| err("vidas no válidas"))?),
+                "check_state" => check_state = Some(value.to_string()),
+                "check_cat" => {
+                    let v: Vec<f32> = value.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+                    let [x, y, direction] = v[..] else { return Err(err("se esperaban x y dirección")) };
+                    check_cats.push((Vec2::new(x, y), direction));
+                }
+                _ => return Err(err(&format!("clave desconocida `{}`", key))),
+            }
+        }
+
+        if replay.levels.is_empty() {
+            return Err("la repetición no tiene niveles".to_string());
+        }
+        if let (Some((player_pos, player_a)), Some(score), Some(health), Some(lives), Some(state)) =
+            (check_player, check_score, check_health, check_lives, check_state)
+        {
+            replay.check = Some(ReplayCheck { player_pos, player_a, score, health, lives, state, cats: check_cats });
+        }
+        Ok(replay)
+    }
+}
+
+fn parse_input(value: &str) -> Option<(u64, Input)> {
+    let mut parts = value.split_whitespace();
+    let tick = parts.next()?.parse().ok()?;
+    let input = match parts.next()? {
+        "key" => {
+            let action = Action::from_name(parts.next()?)?;
+            let pressed = match parts.next()? {
+                "down" => true,
+                "up" => false,
+                _ => return None,
+            };
+            Input::Key { action, pressed }
+        }
+        "look" => Input::Look(parts.next()?.parse().ok()?),
+        "text" => Input::Text(char::from_u32(parts.next()?.parse().ok()?)?),
+        _ => return None,
+    };
+    parts.next().is_none().then_some((tick, input))
+}
+
+// El contenido de un archivo en una sola línea
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        out.push(match c {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            },
+            c => c,
+        });
+    }
+    Some(out)
+}
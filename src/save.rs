@@ -0,0 +1,370 @@
+use nalgebra_glm::Vec2;
+use std::fmt;
+use std::io;
+
+use crate::door::KeyColor;
+use crate::game::View;
+use crate::storage::Storage;
+use crate::world::{MAX_HEALTH, START_LIVES};
+
+// Versión del formato que escribe este ejecutable. Las claves desconocidas se
+// ignoran al leer, así que añadir campos no obliga a cambiarla; solo hace falta
+// subirla cuando un campo cambia de significado
+pub const SAVE_VERSION: u32 = 1;
+// Partidas más antiguas que esta ya no se pueden leer
+pub const MIN_SAVE_VERSION: u32 = 1;
+
+const SAVE_HEADER: &str = "raycaster-save";
+pub const SAVE_DIR: &str = "saves";
+
+// Ranura 0 = guardado rápido; 1..=SAVE_SLOTS las normales
+pub const QUICK_SLOT: usize = 0;
+pub const SAVE_SLOTS: usize = 3;
+
+// Nivel de una partida guardada
+#[derive(Clone, Debug, PartialEq)]
+pub enum SavedLevel {
+    File(String), // Ruta del laberinto, tal como se pasó con `--level`
+    Generated(u64),
+}
+
+// Estado de la partida en curso, lo necesario para seguir jugando
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaveGame {
+    pub level: SavedLevel,
+    pub view: View,
+    pub player_pos: Vec2,
+    pub player_a: f32,
+    pub player_fov: f32,
+    pub score: u32,
+    pub start_score: u32,
+    pub elapsed: f32,              // Segundos jugados en el nivel
+    pub health: u32,
+    pub lives: u32,
+    pub start_lives: u32,
+    pub invulnerable: f32,
+    pub carrots: Vec<(usize, usize)>, // Zanahorias que quedan, (columna, fila)
+    pub hearts: Vec<(usize, usize)>,  // Corazones que quedan, (columna, fila)
+    pub keys: Vec<(usize, usize)>,    // Llaves que quedan en el suelo, (columna, fila)
+    pub held_keys: Vec<KeyColor>,     // Llaves recogidas
+    pub doors: Vec<(usize, usize, f32, bool)>, // Puertas sin abrir del todo: celda, apertura y si se está abriendo
+    pub cats: Vec<(Vec2, f32)>,    // Posición y dirección de cada gato
+    pub explored: Vec<Vec<bool>>,  // Celdas vistas para los mapas, por filas
+    pub collected: Vec<(usize, usize)>, // Zanahorias recogidas, (columna, fila)
+    pub trail: Vec<Vec<Vec2>>,     // Tramos del recorrido del jugador
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    EmptySlot(usize),
+    NotASave,
+    TooNew { version: u32 },
+    TooOld { version: u32 },
+    Corrupt { line: usize, reason: String },
+    MissingField(&'static str),
+    LevelMismatch(String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "error de lectura/escritura: {}", err),
+            SaveError::EmptySlot(slot) => write!(f, "la ranura {} está vacía", slot_label(*slot)),
+            SaveError::NotASave => write!(f, "el archivo no es una partida guardada"),
+            SaveError::TooNew { version } => write!(
+                f,
+                "la partida es de una versión más nueva del juego (formato {}, este lee hasta el {})",
+                version, SAVE_VERSION
+            ),
+            SaveError::TooOld { version } => write!(
+                f,
+                "la partida es de una versión antigua que ya no se puede cargar (formato {}, mínimo {})",
+                version, MIN_SAVE_VERSION
+            ),
+            SaveError::Corrupt { line, reason } => write!(f, "partida dañada en la línea {}: {}", line, reason),
+            SaveError::MissingField(field) => write!(f, "partida dañada: falta `{}`", field),
+            SaveError::LevelMismatch(reason) => write!(f, "la partida no corresponde al nivel: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+// Clave de la ranura en el almacenamiento, `saves/quick.sav` o `saves/slotN.sav`
+pub fn slot_path(slot: usize) -> String {
+    let name = if slot == QUICK_SLOT { "quick.sav".to_string() } else { format!("slot{}.sav", slot) };
+    format!("{}/{}", SAVE_DIR, name)
+}
+
+pub fn slot_label(slot: usize) -> String {
+    if slot == QUICK_SLOT {
+        "rápida".to_string()
+    } else {
+        slot.to_string()
+    }
+}
+
+pub fn write_slot(storage: &mut dyn Storage, save: &SaveGame, slot: usize) -> Result<String, SaveError> {
+    let path = slot_path(slot);
+    storage.write(&path, &save.to_text())?;
+    Ok(path)
+}
+
+pub fn read_slot(storage: &mut dyn Storage, slot: usize) -> Result<SaveGame, SaveError> {
+    match storage.read(&slot_path(slot)) {
+        Ok(text) => SaveGame::parse(&text),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Err(SaveError::EmptySlot(slot)),
+        Err(err) => Err(SaveError::Io(err)),
+    }
+}
+
+// Resumen de una ranura para el menú
+pub fn slot_summary(storage: &mut dyn Storage, slot: usize) -> String {
+    match read_slot(storage, slot) {
+        Ok(save) => {
+            let name = match &save.level {
+                SavedLevel::File(path) => path.clone(),
+                SavedLevel::Generated(seed) => format!("generado-{}", seed),
+            };
+            let secs = save.elapsed as u32;
+            format!("{} · {} pts · {}:{:02}", name, save.score, secs / 60, secs % 60)
+        }
+        Err(SaveError::EmptySlot(_)) => "vacía".to_string(),
+        Err(SaveError::TooNew { .. }) => "versión más nueva".to_string(),
+        Err(SaveError::TooOld { .. }) => "versión antigua".to_string(),
+        Err(_) => "dañada".to_string(),
+    }
+}
+
+impl SaveGame {
+    // Formato de texto: cabecera con la versión y luego `clave = valor`;
+    // `carrot`, `heart`, `key`, `door` y `cat` se repiten una vez por elemento
+    // y `explored`, una vez por fila del laberinto. Cada `trail` es un tramo
+    // del recorrido: pares x y seguidos
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{} {}\n", SAVE_HEADER, SAVE_VERSION);
+        let level = match &self.level {
+            SavedLevel::File(path) => format!("file {}", path),
+            SavedLevel::Generated(seed) => format!("generated {}", seed),
+        };
+        text += &format!("level = {}\n", level);
+        text += &format!("view = {}\n", if self.view == View::TwoD { "2d" } else { "3d" });
+        text += &format!(
+            "player = {} {} {} {}\n",
+            self.player_pos.x, self.player_pos.y, self.player_a, self.player_fov
+        );
+        text += &format!("score = {}\n", self.score);
+        text += &format!("start_score = {}\n", self.start_score);
+        text += &format!("elapsed = {}\n", self.elapsed);
+        text += &format!("health = {} {} {}\n", self.health, self.lives, self.start_lives);
+        text += &format!("invulnerable = {}\n", self.invulnerable);
+        for (col, row) in &self.carrots {
+            text += &format!("carrot = {} {}\n", col, row);
+        }
+        for (col, row) in &self.hearts {
+            text += &format!("heart = {} {}\n", col, row);
+        }
+        for (col, row) in &self.keys {
+            text += &format!("key = {} {}\n", col, row);
+        }
+        let held: String = self.held_keys.iter().map(|k| k.key_tile()).collect();
+        text += &format!("held_keys = {}\n", held);
+        for (col, row, open, opening) in &self.doors {
+            text += &format!("door = {} {} {} {}\n", col, row, open, u8::from(*opening));
+        }
+        for (pos, direction) in &self.cats {
+            text += &format!("cat = {} {} {}\n", pos.x, pos.y, direction);
+        }
+        for line in &self.explored {
+            let row: String = line.iter().map(|&seen| if seen { '1' } else { '0' }).collect();
+            text += &format!("explored = {}\n", row);
+        }
+        for (col, row) in &self.collected {
+            text += &format!("collected = {} {}\n", col, row);
+        }
+        for segment in &self.trail {
+            let points: Vec<String> = segment.iter().map(|p| format!("{} {}", p.x, p.y)).collect();
+            text += &format!("trail = {}\n", points.join(" "));
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Self, SaveError> {
+        let mut lines = text.lines().enumerate();
+        let version = lines
+            .next()
+            .and_then(|(_, header)| header.strip_prefix(SAVE_HEADER))
+            .and_then(|version| version.trim().parse::<u32>().ok())
+            .ok_or(SaveError::NotASave)?;
+        if version > SAVE_VERSION {
+            return Err(SaveError::TooNew { version });
+        }
+        if version < MIN_SAVE_VERSION {
+            return Err(SaveError::TooOld { version });
+        }
+
+        let mut level = None;
+        let mut view = None;
+        let mut player = None;
+        let mut score = None;
+        let mut start_score = None;
+        let mut elapsed = None;
+        let mut health = None;
+        let mut invulnerable = None;
+        let mut carrots = Vec::new();
+        let mut hearts = Vec::new();
+        let mut keys = Vec::new();
+        let mut held_keys = Vec::new();
+        let mut doors = Vec::new();
+        let mut cats = Vec::new();
+        let mut explored = Vec::new();
+        let mut collected = Vec::new();
+        let mut trail = Vec::new();
+
+        for (idx, line) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let corrupt = |reason: &str| SaveError::Corrupt { line: idx + 1, reason: reason.to_string() };
+            let (key, value) = line.split_once('=').ok_or_else(|| corrupt("se esperaba `clave = valor`"))?;
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "level" => {
+                    level = Some(match value.split_once(' ') {
+                        Some(("file", path)) => SavedLevel::File(path.trim().to_string()),
+                        Some(("generated", seed)) => {
+                            SavedLevel::Generated(seed.trim().parse().map_err(|_| corrupt("semilla no válida"))?)
+                        }
+                        _ => return Err(corrupt("nivel no válido")),
+                    })
+                }
+                "view" => {
+                    view = Some(match value {
+                        "2d" => View::TwoD,
+                        "3d" => View::ThreeD,
+                        _ => return Err(corrupt("vista no válida")),
+                    })
+                }
+                "player" => {
+                    let v = parse_floats::<4>(value).ok_or_else(|| corrupt("se esperaban x y ángulo fov"))?;
+                    player = Some(v);
+                }
+                "score" => score = Some(value.parse().map_err(|_| corrupt("puntos no válidos"))?),
+                "start_score" => start_score = Some(value.parse().map_err(|_| corrupt("puntos no válidos"))?),
+                "elapsed" => elapsed = Some(parse_floats::<1>(value).ok_or_else(|| corrupt("tiempo no válido"))?[0]),
+                "health" => {
+                    let mut parts = value.split_whitespace().map(str::parse::<u32>);
+                    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                        (Some(Ok(h)), Some(Ok(lives)), Some(Ok(start_lives)), None) => {
+                            health = Some((h, lives, start_lives))
+                        }
+                        _ => return Err(corrupt("se esperaban corazones, vidas y vidas al empezar")),
+                    }
+                }
+                "invulnerable" => invulnerable = Some(parse_floats::<1>(value).ok_or_else(|| corrupt("tiempo no válido"))?[0]),
+                "carrot" | "heart" | "key" | "collected" => {
+                    let mut parts = value.split_whitespace().map(str::parse::<usize>);
+                    let cell = match (parts.next(), parts.next(), parts.next()) {
+                        (Some(Ok(col)), Some(Ok(row)), None) => (col, row),
+                        _ => return Err(corrupt("se esperaban columna y fila")),
+                    };
+                    match key {
+                        "carrot" => carrots.push(cell),
+                        "heart" => hearts.push(cell),
+                        "key" => keys.push(cell),
+                        _ => collected.push(cell),
+                    }
+                }
+                "held_keys" => {
+                    held_keys = value
+                        .chars()
+                        .map(KeyColor::from_key_tile)
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| corrupt("llave no válida"))?
+                }
+                "door" => {
+                    let parts: Vec<&str> = value.split_whitespace().collect();
+                    let door = match parts[..] {
+                        [col, row, open, opening] => (|| {
+                            let open: f32 = open.parse().ok().filter(|o| (0.0..1.0).contains(o))?;
+                            Some((col.parse().ok()?, row.parse().ok()?, open, opening == "1"))
+                        })(),
+                        _ => None,
+                    };
+                    doors.push(door.ok_or_else(|| corrupt("se esperaban columna, fila, apertura y 0/1"))?);
+                }
+                "cat" => {
+                    let [x, y, direction] =
+                        parse_floats::<3>(value).ok_or_else(|| corrupt("se esperaban x y dirección"))?;
+                    cats.push((Vec2::new(x, y), direction));
+                }
+                "explored" => {
+                    let row = value
+                        .chars()
+                        .map(|c| match c {
+                            '0' => Some(false),
+                            '1' => Some(true),
+                            _ => None,
+                        })
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| corrupt("se esperaban solo 0 y 1"))?;
+                    explored.push(row);
+                }
+                "trail" => {
+                    let coords = value
+                        .split_whitespace()
+                        .map(|v| v.parse().ok().filter(|v: &f32| v.is_finite()))
+                        .collect::<Option<Vec<f32>>>()
+                        .filter(|coords| coords.len() % 2 == 0)
+                        .ok_or_else(|| corrupt("se esperaban pares x y"))?;
+                    trail.push(coords.chunks(2).map(|p| Vec2::new(p[0], p[1])).collect());
+                }
+                _ => {} // Campos de versiones más nuevas
+            }
+        }
+
+        let [x, y, a, fov] = player.ok_or(SaveError::MissingField("player"))?;
+        // Las partidas de antes de la vida empiezan con todo lleno
+        let (health, lives, start_lives) = health.unwrap_or((MAX_HEALTH, START_LIVES, START_LIVES));
+        Ok(Self {
+            level: level.ok_or(SaveError::MissingField("level"))?,
+            view: view.ok_or(SaveError::MissingField("view"))?,
+            player_pos: Vec2::new(x, y),
+            player_a: a,
+            player_fov: fov,
+            score: score.ok_or(SaveError::MissingField("score"))?,
+            start_score: start_score.unwrap_or(0),
+            elapsed: elapsed.unwrap_or(0.0),
+            health,
+            lives,
+            start_lives,
+            invulnerable: invulnerable.unwrap_or(0.0),
+            carrots,
+            hearts,
+            keys,
+            held_keys,
+            doors,
+            cats,
+            explored,
+            collected,
+            trail,
+        })
+    }
+}
+
+fn parse_floats<const N: usize>(text: &str) -> Option<[f32; N]> {
+    let mut out = [0.0; N];
+    let mut parts = text.split_whitespace();
+    for value in out.iter_mut() {
+        *value = parts.next()?.parse().ok().filter(|v: &f32| v.is_finite())?;
+    }
+    parts.next().is_none().then_some(out)
+}
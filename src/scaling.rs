@@ -0,0 +1,160 @@
+use rayon::prelude::*;
+
+// Filtro usado al ampliar la imagen interna a la ventana
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScaleFilter {
+    Nearest,
+    Linear,
+}
+
+impl ScaleFilter {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "nearest" => Some(ScaleFilter::Nearest),
+            "linear" => Some(ScaleFilter::Linear),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ScaleFilter::Nearest => "nearest",
+            ScaleFilter::Linear => "linear",
+        }
+    }
+}
+
+// Resolución interna del render, independiente del tamaño de la ventana
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderResolution {
+    Fixed(usize, usize),
+    Native, // La misma que la ventana
+}
+
+// Resoluciones que se recorren con la tecla de cambio de resolución
+pub const RESOLUTION_PRESETS: [RenderResolution; 5] = [
+    RenderResolution::Fixed(320, 200),
+    RenderResolution::Fixed(640, 400),
+    RenderResolution::Fixed(960, 600),
+    RenderResolution::Fixed(1300, 900),
+    RenderResolution::Native,
+];
+
+impl RenderResolution {
+    // Acepta "ANCHOxALTO" o "native"
+    pub fn parse(text: &str) -> Option<Self> {
+        if text == "native" {
+            return Some(RenderResolution::Native);
+        }
+        let (w, h) = text.split_once('x')?;
+        let (w, h) = (w.parse().ok()?, h.parse().ok()?);
+        if w == 0 || h == 0 {
+            return None;
+        }
+        Some(RenderResolution::Fixed(w, h))
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            RenderResolution::Fixed(w, h) => format!("{}x{}", w, h),
+            RenderResolution::Native => "native".to_string(),
+        }
+    }
+
+    pub fn size(self, window_width: usize, window_height: usize) -> (usize, usize) {
+        match self {
+            RenderResolution::Fixed(w, h) => (w, h),
+            RenderResolution::Native => (window_width.max(1), window_height.max(1)),
+        }
+    }
+
+    // Siguiente resolución de `RESOLUTION_PRESETS`
+    pub fn next(self) -> Self {
+        let idx = RESOLUTION_PRESETS.iter().position(|&r| r == self);
+        match idx {
+            Some(i) => RESOLUTION_PRESETS[(i + 1) % RESOLUTION_PRESETS.len()],
+            None => RESOLUTION_PRESETS[0],
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        let idx = RESOLUTION_PRESETS.iter().position(|&r| r == self);
+        match idx {
+            Some(i) => RESOLUTION_PRESETS[(i + RESOLUTION_PRESETS.len() - 1) % RESOLUTION_PRESETS.len()],
+            None => RESOLUTION_PRESETS[0],
+        }
+    }
+}
+
+// Área de la ventana donde cabe la imagen interna sin deformarse
+pub fn fit_rect(src_w: usize, src_h: usize, dst_w: usize, dst_h: usize) -> (usize, usize, usize, usize) {
+    let scale = (dst_w as f32 / src_w as f32).min(dst_h as f32 / src_h as f32);
+    let out_w = ((src_w as f32 * scale) as usize).clamp(1, dst_w);
+    let out_h = ((src_h as f32 * scale) as usize).clamp(1, dst_h);
+    ((dst_w - out_w) / 2, (dst_h - out_h) / 2, out_w, out_h)
+}
+
+// Amplía (o reduce) `src` a `dst` manteniendo la proporción; lo que sobra
+// queda en negro
+pub fn upscale(src: &[u8], src_w: usize, src_h: usize, dst: &mut [u8], dst_w: usize, dst_h: usize, filter: ScaleFilter) {
+    if src_w == dst_w && src_h == dst_h {
+        let len = (dst_w * dst_h * 4).min(src.len()).min(dst.len());
+        dst[..len].copy_from_slice(&src[..len]);
+        return;
+    }
+
+    let (off_x, off_y, out_w, out_h) = fit_rect(src_w, src_h, dst_w, dst_h);
+    let step_x = src_w as f32 / out_w as f32;
+    let step_y = src_h as f32 / out_h as f32;
+
+    dst.par_chunks_mut(dst_w * 4).take(dst_h).enumerate().for_each(|(y, row)| {
+        if y < off_y || y >= off_y + out_h {
+            row.fill(0);
+            for pixel in row.chunks_exact_mut(4) {
+                pixel[3] = 0xFF;
+            }
+            return;
+        }
+
+        let sy = ((y - off_y) as f32 + 0.5) * step_y;
+        for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+            if x < off_x || x >= off_x + out_w {
+                pixel.copy_from_slice(&[0, 0, 0, 0xFF]);
+                continue;
+            }
+            let sx = ((x - off_x) as f32 + 0.5) * step_x;
+            let color = match filter {
+                ScaleFilter::Nearest => sample(src, src_w, sx as usize, sy as usize),
+                ScaleFilter::Linear => sample_linear(src, src_w, src_h, sx, sy),
+            };
+            pixel.copy_from_slice(&color);
+        }
+    });
+}
+
+fn sample(src: &[u8], src_w: usize, x: usize, y: usize) -> [u8; 4] {
+    let i = (y * src_w + x) * 4;
+    [src[i], src[i + 1], src[i + 2], src[i + 3]]
+}
+
+fn sample_linear(src: &[u8], src_w: usize, src_h: usize, sx: f32, sy: f32) -> [u8; 4] {
+    // Centro del píxel de origen
+    let fx = (sx - 0.5).max(0.0);
+    let fy = (sy - 0.5).max(0.0);
+    let x0 = (fx as usize).min(src_w - 1);
+    let y0 = (fy as usize).min(src_h - 1);
+    let x1 = (x0 + 1).min(src_w - 1);
+    let y1 = (y0 + 1).min(src_h - 1);
+    let tx = fx - x0 as f32;
+    let ty = fy - y0 as f32;
+
+    let (a, b) = (sample(src, src_w, x0, y0), sample(src, src_w, x1, y0));
+    let (c, d) = (sample(src, src_w, x0, y1), sample(src, src_w, x1, y1));
+    let mut out = [0u8; 4];
+    for (k, channel) in out.iter_mut().enumerate() {
+        let top = a[k] as f32 + (b[k] as f32 - a[k] as f32) * tx;
+        let bottom = c[k] as f32 + (d[k] as f32 - c[k] as f32) * tx;
+        *channel = (top + (bottom - top) * ty) as u8;
+    }
+    out
+}
@@ -0,0 +1,220 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::minimap::{zoom_label, MinimapMode, MINIMAP_ZOOMS};
+use crate::scaling::RenderResolution;
+use crate::world::{WORLD_HEIGHT, WORLD_WIDTH};
+
+// Archivo donde se guardan las opciones, junto al ejecutable
+pub const SETTINGS_FILE: &str = "settings.cfg";
+
+// Campo de visión que permite el menú, en grados
+pub const FOV_MIN_DEGREES: f32 = 40.0;
+pub const FOV_MAX_DEGREES: f32 = 120.0;
+
+// Opciones del jugador que se pueden cambiar desde el menú de pausa
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    pub fov_degrees: f32,
+    pub mouse_sensitivity: f32, // Radianes por píxel de ratón
+    pub music_volume: f32,      // 0.0 - 1.0
+    pub sfx_volume: f32,        // 0.0 - 1.0
+    pub footsteps_volume: f32,  // 0.0 - 1.0
+    pub resolution: RenderResolution,
+    pub fog: bool,
+    pub minimap: MinimapMode,
+    pub minimap_zoom: usize, // Índice en `MINIMAP_ZOOMS`
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fov_degrees: 60.0,
+            mouse_sensitivity: 0.005,
+            music_volume: 0.1,
+            sfx_volume: 0.8,
+            footsteps_volume: 0.9,
+            resolution: RenderResolution::Fixed(WORLD_WIDTH, WORLD_HEIGHT),
+            fog: false,
+            minimap: MinimapMode::Rotating,
+            minimap_zoom: 1,
+        }
+    }
+}
+
+// Cada una de las opciones, en el orden en que aparecen en el menú
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingItem {
+    Fov,
+    MouseSensitivity,
+    MusicVolume,
+    SfxVolume,
+    FootstepsVolume,
+    Resolution,
+    Fog,
+    Minimap,
+    MinimapZoom,
+}
+
+pub const SETTING_ITEMS: [SettingItem; 9] = [
+    SettingItem::Fov,
+    SettingItem::MouseSensitivity,
+    SettingItem::MusicVolume,
+    SettingItem::SfxVolume,
+    SettingItem::FootstepsVolume,
+    SettingItem::Resolution,
+    SettingItem::Fog,
+    SettingItem::Minimap,
+    SettingItem::MinimapZoom,
+];
+
+// Claves de `settings.cfg`, en el orden en que se escriben
+pub const SETTING_KEYS: [&str; 9] = [
+    "fov",
+    "mouse_sensitivity",
+    "music_volume",
+    "sfx_volume",
+    "footsteps_volume",
+    "resolution",
+    "fog",
+    "minimap",
+    "minimap_zoom",
+];
+
+impl SettingItem {
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingItem::Fov => "Campo de visión",
+            SettingItem::MouseSensitivity => "Sensibilidad del ratón",
+            SettingItem::MusicVolume => "Volumen de la música",
+            SettingItem::SfxVolume => "Volumen de los efectos",
+            SettingItem::FootstepsVolume => "Volumen de los pasos",
+            SettingItem::Resolution => "Resolución",
+            SettingItem::Fog => "Niebla",
+            SettingItem::Minimap => "Minimapa",
+            SettingItem::MinimapZoom => "Zoom del minimapa",
+        }
+    }
+}
+
+impl Settings {
+    pub fn fov(&self) -> f32 {
+        self.fov_degrees.to_radians()
+    }
+
+    // Sube (`step` > 0) o baja (`step` < 0) una opción dentro de su rango
+    pub fn adjust(&mut self, item: SettingItem, step: i32) {
+        let step = step as f32;
+        match item {
+            SettingItem::Fov => {
+                self.fov_degrees = (self.fov_degrees + step * 5.0).clamp(FOV_MIN_DEGREES, FOV_MAX_DEGREES)
+            }
+            SettingItem::MouseSensitivity => {
+                self.mouse_sensitivity = (self.mouse_sensitivity + step * 0.001).clamp(0.001, 0.02)
+            }
+            SettingItem::MusicVolume => self.music_volume = volume_step(self.music_volume, step),
+            SettingItem::SfxVolume => self.sfx_volume = volume_step(self.sfx_volume, step),
+            SettingItem::FootstepsVolume => self.footsteps_volume = volume_step(self.footsteps_volume, step),
+            SettingItem::Resolution => {
+                self.resolution = if step > 0.0 { self.resolution.next() } else { self.resolution.previous() }
+            }
+            SettingItem::Fog => self.fog = !self.fog,
+            SettingItem::Minimap => {
+                self.minimap = if step > 0.0 { self.minimap.next() } else { self.minimap.previous() }
+            }
+            SettingItem::MinimapZoom => {
+                self.minimap_zoom = (self.minimap_zoom as i32 + step as i32).clamp(0, MINIMAP_ZOOMS.len() as i32 - 1) as usize
+            }
+        }
+    }
+
+    // Valor tal como se muestra en el menú
+    pub fn value_label(&self, item: SettingItem) -> String {
+        match item {
+            SettingItem::Fov => format!("{:.0}°", self.fov_degrees),
+            SettingItem::MouseSensitivity => format!("{:.3}", self.mouse_sensitivity),
+            SettingItem::MusicVolume => format!("{:.0}%", self.music_volume * 100.0),
+            SettingItem::SfxVolume => format!("{:.0}%", self.sfx_volume * 100.0),
+            SettingItem::FootstepsVolume => format!("{:.0}%", self.footsteps_volume * 100.0),
+            SettingItem::Resolution => self.resolution.label(),
+            SettingItem::Fog => if self.fog { "Sí" } else { "No" }.to_string(),
+            SettingItem::Minimap => self.minimap.label().to_string(),
+            SettingItem::MinimapZoom => zoom_label(self.minimap_zoom).to_string(),
+        }
+    }
+
+    // Lee el archivo de opciones; si no existe se usan las de por defecto
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    // Formato `clave = valor`, una opción por línea; las líneas con `#` son
+    // comentarios y los valores que no se entienden se dejan por defecto
+    pub fn parse(text: &str) -> Self {
+        let mut settings = Self::default();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                eprintln!("Opción ignorada: {}", line);
+                continue;
+            };
+            if !settings.set(key.trim(), value.trim()) {
+                eprintln!("Opción ignorada: {}", line);
+            }
+        }
+        settings
+    }
+
+    // Cambia la opción `key` como si viniera de `settings.cfg`; devuelve
+    // `false` si la clave no existe o el valor no vale
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        match key {
+            "fov" => value.parse().map(|v: f32| self.fov_degrees = v.clamp(FOV_MIN_DEGREES, FOV_MAX_DEGREES)).is_ok(),
+            "mouse_sensitivity" => value.parse().map(|v: f32| self.mouse_sensitivity = v.clamp(0.001, 0.02)).is_ok(),
+            "music_volume" => value.parse().map(|v: f32| self.music_volume = v.clamp(0.0, 1.0)).is_ok(),
+            "sfx_volume" => value.parse().map(|v: f32| self.sfx_volume = v.clamp(0.0, 1.0)).is_ok(),
+            "footsteps_volume" => value.parse().map(|v: f32| self.footsteps_volume = v.clamp(0.0, 1.0)).is_ok(),
+            "resolution" => RenderResolution::parse(value).map(|r| self.resolution = r).is_some(),
+            "fog" => value.parse().map(|v| self.fog = v).is_ok(),
+            "minimap" => MinimapMode::from_name(value).map(|m| self.minimap = m).is_some(),
+            "minimap_zoom" => value
+                .parse()
+                .ok()
+                .filter(|&z: &usize| z < MINIMAP_ZOOMS.len())
+                .map(|z| self.minimap_zoom = z)
+                .is_some(),
+            _ => false,
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        format!(
+            "# Opciones del juego\nfov = {}\nmouse_sensitivity = {}\nmusic_volume = {}\nsfx_volume = {}\nfootsteps_volume = {}\nresolution = {}\nfog = {}\nminimap = {}\nminimap_zoom = {}\n",
+            self.fov_degrees,
+            self.mouse_sensitivity,
+            self.music_volume,
+            self.sfx_volume,
+            self.footsteps_volume,
+            self.resolution.label(),
+            self.fog,
+            self.minimap.name(),
+            self.minimap_zoom,
+        )
+    }
+}
+
+// Pasos de 10 % sin acumular error de redondeo
+fn volume_step(volume: f32, step: f32) -> f32 {
+    ((volume * 10.0).round() + step).clamp(0.0, 10.0) / 10.0
+}
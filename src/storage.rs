@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// Dónde guarda el juego las partidas y las tablas de récords. Las claves son
+// rutas relativas como `saves/quick.sav`; la ventana usa archivos de verdad y
+// las repeticiones y la simulación, memoria, para no tocar los del jugador
+pub trait Storage {
+    // Contenido de `key`; un error `NotFound` si no existe
+    fn read(&mut self, key: &str) -> io::Result<String>;
+    fn write(&mut self, key: &str, text: &str) -> io::Result<()>;
+}
+
+// Archivos debajo de `root`
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Default for FileStorage {
+    // Junto al ejecutable, como `settings.cfg`
+    fn default() -> Self {
+        Self::new(".")
+    }
+}
+
+impl Storage for FileStorage {
+    fn read(&mut self, key: &str) -> io::Result<String> {
+        fs::read_to_string(self.root.join(key))
+    }
+
+    fn write(&mut self, key: &str, text: &str) -> io::Result<()> {
+        let path = self.root.join(key);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Primero a un archivo temporal para no dejar medio archivo si algo falla
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(&tmp, &path)
+    }
+}
+
+// Archivos en memoria; se pierden al terminar
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    pub files: BTreeMap<String, String>,
+}
+
+impl Storage for MemoryStorage {
+    fn read(&mut self, key: &str) -> io::Result<String> {
+        self.files.get(key).cloned().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, key.to_string()))
+    }
+
+    fn write(&mut self, key: &str, text: &str) -> io::Result<()> {
+        self.files.insert(key.to_string(), text.to_string());
+        Ok(())
+    }
+}
+
+// Lo que había en cada archivo la primera vez que se leyó (`None` si no
+// existía). Con esto una repetición parte de los mismos archivos que la
+// partida grabada
+pub type FirstReads = Vec<(String, Option<String>)>;
+
+// `inner` apuntando en `log` cada primera lectura, si hay log
+pub struct LoggedStorage<'a> {
+    pub inner: &'a mut dyn Storage,
+    pub log: Option<&'a mut FirstReads>,
+}
+
+impl Storage for LoggedStorage<'_> {
+    fn read(&mut self, key: &str) -> io::Result<String> {
+        let result = self.inner.read(key);
+        if let Some(log) = &mut self.log {
+            if !log.iter().any(|(k, _)| k == key) {
+                log.push((key.to_string(), result.as_ref().ok().cloned()));
+            }
+        }
+        result
+    }
+
+    fn write(&mut self, key: &str, text: &str) -> io::Result<()> {
+        self.inner.write(key, text)
+    }
+}
@@ -0,0 +1,262 @@
+use once_cell::sync::Lazy;
+use rusttype::{point, Font, Scale};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::framebuffer::Framebuffer;
+
+// Fuente del juego, leída una sola vez
+pub static TEXT: Lazy<TextRenderer> = Lazy::new(|| {
+    let font_data = include_bytes!("../assets/Typold-Book500.ttf");
+    TextRenderer::new(font_data).expect("Error cargando la fuente")
+});
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+// Cómo se dibuja un texto: tamaño, color, alineación, sombra y ancho máximo
+#[derive(Clone, Copy, Debug)]
+pub struct TextStyle {
+    pub size: f32,
+    pub color: [u8; 4],
+    pub align: Align,
+    pub shadow: Option<([u8; 4], isize)>, // (color, desplazamiento en píxeles)
+    pub max_width: Option<usize>,         // Ancho para partir líneas
+}
+
+impl TextStyle {
+    pub fn new(size: f32) -> Self {
+        Self {
+            size,
+            color: [0xFF, 0xFF, 0xFF, 0xFF],
+            align: Align::Left,
+            shadow: None,
+            max_width: None,
+        }
+    }
+
+    pub fn color(mut self, color: [u8; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn shadow(mut self, color: [u8; 4], offset: isize) -> Self {
+        self.shadow = Some((color, offset));
+        self
+    }
+
+    pub fn wrap(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+}
+
+// Mapa de cobertura de un glifo ya rasterizado
+struct GlyphBitmap {
+    min_x: i32,
+    min_y: i32, // Relativo a la línea base
+    width: usize,
+    height: usize,
+    coverage: Vec<u8>,
+    advance: f32,
+}
+
+// Glifos que se guardan rasterizados. Cada tamaño de ventana da tamaños de
+// letra nuevos, así que al llenarse se tiran los que hace más que no se usan
+pub const MAX_CACHED_GLYPHS: usize = 1024;
+
+// Glifos por (carácter, tamaño en cuartos de píxel), con el momento en que se
+// usaron por última vez
+#[derive(Default)]
+struct GlyphCache {
+    glyphs: HashMap<(char, u32), (Arc<GlyphBitmap>, u64)>,
+    clock: u64,
+}
+
+pub struct TextRenderer {
+    font: Font<'static>,
+    cache: Mutex<GlyphCache>,
+}
+
+impl TextRenderer {
+    pub fn new(font_data: &'static [u8]) -> Option<Self> {
+        let font = Font::try_from_bytes(font_data)?;
+        Some(Self { font, cache: Mutex::new(GlyphCache::default()) })
+    }
+
+    fn glyph(&self, c: char, size: f32) -> Arc<GlyphBitmap> {
+        let key = (c, (size * 4.0).round() as u32);
+        let mut cache = self.cache.lock().unwrap();
+        cache.clock += 1;
+        let now = cache.clock;
+        if let Some((glyph, used)) = cache.glyphs.get_mut(&key) {
+            *used = now;
+            return Arc::clone(glyph);
+        }
+        if cache.glyphs.len() >= MAX_CACHED_GLYPHS {
+            // Se queda con los tres cuartos usados más recientemente
+            let mut ages: Vec<u64> = cache.glyphs.values().map(|(_, used)| *used).collect();
+            ages.sort_unstable();
+            let cutoff = ages[ages.len() / 4];
+            cache.glyphs.retain(|_, (_, used)| *used > cutoff);
+        }
+        let glyph = Arc::new(self.rasterize(c, size));
+        cache.glyphs.insert(key, (Arc::clone(&glyph), now));
+        glyph
+    }
+
+    // Glifos rasterizados que hay ahora en la caché
+    pub fn cached_glyphs(&self) -> usize {
+        self.cache.lock().unwrap().glyphs.len()
+    }
+
+    fn rasterize(&self, c: char, size: f32) -> GlyphBitmap {
+        let scale = Scale::uniform(size);
+        let glyph = self.font.glyph(c).scaled(scale);
+        let advance = glyph.h_metrics().advance_width;
+        let glyph = glyph.positioned(point(0.0, 0.0));
+
+        let Some(bb) = glyph.pixel_bounding_box() else {
+            return GlyphBitmap { min_x: 0, min_y: 0, width: 0, height: 0, coverage: Vec::new(), advance };
+        };
+
+        let width = bb.width() as usize;
+        let height = bb.height() as usize;
+        let mut coverage = vec![0u8; width * height];
+        glyph.draw(|gx, gy, v| {
+            coverage[gy as usize * width + gx as usize] = (v * 255.0) as u8;
+        });
+
+        GlyphBitmap { min_x: bb.min.x, min_y: bb.min.y, width, height, coverage, advance }
+    }
+
+    pub fn line_height(&self, size: f32) -> f32 {
+        let v_metrics = self.font.v_metrics(Scale::uniform(size));
+        v_metrics.ascent - v_metrics.descent + v_metrics.line_gap
+    }
+
+    // Avance de `c` tras `prev`, sin rasterizar ni pasar por la caché
+    fn advance(&self, prev: Option<char>, c: char, scale: Scale) -> f32 {
+        let kerning = prev.map_or(0.0, |p| self.font.pair_kerning(scale, p, c));
+        kerning + self.font.glyph(c).scaled(scale).h_metrics().advance_width
+    }
+
+    fn line_width(&self, line: &str, size: f32) -> f32 {
+        let scale = Scale::uniform(size);
+        let mut width = 0.0;
+        let mut prev = None;
+        for c in line.chars() {
+            width += self.advance(prev, c, scale);
+            prev = Some(c);
+        }
+        width
+    }
+
+    // Parte el texto en líneas por '\n' y, si hay ancho máximo, por palabras
+    fn layout_lines<'t>(&self, text: &'t str, style: &TextStyle) -> Vec<&'t str> {
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            let Some(max_width) = style.max_width else {
+                lines.push(paragraph);
+                continue;
+            };
+
+            // Se mide una sola vez cada carácter: el ancho de la línea en curso
+            // y el de lo que va detrás del último espacio, que pasa a ser la
+            // línea siguiente si hay que partir
+            let scale = Scale::uniform(style.size);
+            let mut start = 0;
+            let mut last_break = None;
+            let (mut width, mut prev) = (0.0, None);
+            let (mut after_break, mut prev_after) = (0.0, None);
+            for (idx, c) in paragraph.char_indices() {
+                width += self.advance(prev, c, scale);
+                prev = Some(c);
+                if c == ' ' {
+                    last_break = Some(idx);
+                    (after_break, prev_after) = (0.0, None);
+                } else {
+                    after_break += self.advance(prev_after, c, scale);
+                    prev_after = Some(c);
+                }
+                if width > max_width as f32 {
+                    if let Some(brk) = last_break.filter(|&b| b > start) {
+                        lines.push(&paragraph[start..brk]);
+                        start = brk + 1;
+                        last_break = None;
+                        width = after_break;
+                    }
+                }
+            }
+            lines.push(&paragraph[start..]);
+        }
+        lines
+    }
+
+    // Tamaño en píxeles (ancho, alto) que ocupa el texto con este estilo
+    pub fn measure(&self, text: &str, style: &TextStyle) -> (usize, usize) {
+        let lines = self.layout_lines(text, style);
+        let width = lines
+            .iter()
+            .map(|line| self.line_width(line, style.size))
+            .fold(0.0, f32::max);
+        let height = self.line_height(style.size) * lines.len() as f32;
+        (width.ceil() as usize, height.ceil() as usize)
+    }
+
+    // Dibuja el texto; `x` es el borde izquierdo, el centro o el borde derecho
+    // según la alineación, e `y` la parte superior de la primera línea
+    pub fn draw(&self, framebuffer: &mut Framebuffer, text: &str, x: isize, y: isize, style: &TextStyle) {
+        if let Some((shadow_color, offset)) = style.shadow {
+            let shadow = TextStyle { shadow: None, color: shadow_color, ..*style };
+            self.draw(framebuffer, text, x + offset, y + offset, &shadow);
+        }
+
+        let scale = Scale::uniform(style.size);
+        let ascent = self.font.v_metrics(scale).ascent;
+        let line_height = self.line_height(style.size);
+        let alpha = style.color[3] as f32 / 255.0;
+
+        for (line_idx, line) in self.layout_lines(text, style).into_iter().enumerate() {
+            let line_width = self.line_width(line, style.size);
+            let mut pen_x = match style.align {
+                Align::Left => x as f32,
+                Align::Center => x as f32 - line_width / 2.0,
+                Align::Right => x as f32 - line_width,
+            };
+            let baseline = (y as f32 + ascent + line_height * line_idx as f32).round() as isize;
+
+            let mut prev = None;
+            for c in line.chars() {
+                if let Some(p) = prev {
+                    pen_x += self.font.pair_kerning(scale, p, c);
+                }
+                let glyph = self.glyph(c, style.size);
+                let origin_x = pen_x.round() as isize + glyph.min_x as isize;
+                let origin_y = baseline + glyph.min_y as isize;
+
+                for gy in 0..glyph.height {
+                    for gx in 0..glyph.width {
+                        let v = glyph.coverage[gy * glyph.width + gx];
+                        if v > 0 {
+                            let coverage = v as f32 / 255.0 * alpha;
+                            framebuffer.blend_pixel(origin_x + gx as isize, origin_y + gy as isize, style.color, coverage);
+                        }
+                    }
+                }
+
+                pen_x += glyph.advance;
+                prev = Some(c);
+            }
+        }
+    }
+}
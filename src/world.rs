@@ -0,0 +1,515 @@
+use nalgebra_glm::Vec2;
+use std::collections::VecDeque;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::cast_ray::trace_ray;
+use crate::door::{find_doors, Door, KeyColor, DOOR_OPEN_TIME};
+use crate::maze::{add_locked_door, generate_maze, is_floor, load_maze, Floor};
+use crate::player::Player;
+
+// Tamaño lógico del mundo: de aquí sale `block_size` para cualquier laberinto
+pub const WORLD_WIDTH: usize = 1300;
+pub const WORLD_HEIGHT: usize = 900;
+
+const MOVE_STEP: f32 = 10.0;
+const TURN_STEP: f32 = std::f32::consts::PI / 10.0;
+
+// Vida: corazones por vida y vidas por partida
+pub const MAX_HEALTH: u32 = 3;
+pub const START_LIVES: u32 = 3;
+const CAT_DAMAGE: u32 = 1;
+// Segundos sin recibir daño después de un golpe
+pub const INVULNERABLE_TIME: f32 = 1.5;
+// Duración del destello rojo al recibir daño
+pub const HURT_FLASH_TIME: f32 = 0.3;
+// Distancia del empujón, en bloques; más de uno para salir del alcance del gato
+const KNOCKBACK_BLOCKS: f32 = 1.2;
+// Alcance de la tecla de interactuar, en bloques desde el jugador
+const INTERACT_BLOCKS: f32 = 1.5;
+// Dónde empieza un gato en los archivos de laberinto
+pub const CAT_TILE: char = 'c';
+// Segundos entre maullidos de cada gato, y desfase entre un gato y el siguiente
+const CAT_MEOW_INTERVAL: f32 = 4.0;
+const CAT_MEOW_STAGGER: f32 = 1.3;
+// Distancia entre dos pasos, en bloques
+const STRIDE_BLOCKS: f32 = 0.45;
+// Segundos sobre los que se mide la velocidad del jugador. El conejo avanza a
+// saltos, uno por pulsación, así que tiene que cubrir la pausa de antes de que
+// se repita la tecla; con la velocidad a cero el primer paso suena enseguida
+const SPEED_WINDOW: f32 = 0.6;
+// Rayos con los que se descubre el mapa en cada paso de simulación
+const VISION_RAYS: usize = 48;
+// Separación entre los puntos del recorrido que se guardan para el automapa, en bloques
+const TRAIL_SPACING_BLOCKS: f32 = 0.25;
+
+// Gato que patrulla entre dos puntos
+#[derive(Clone, Debug)]
+pub struct Cat {
+    pub pos: Vec2,
+    pub start: Vec2,
+    pub end: Vec2,
+    pub direction: f32, // 1 = hacia `end`, -1 = hacia `start`
+    pub speed: f32,
+}
+
+impl Cat {
+    pub fn new(start: Vec2, end: Vec2, speed: f32) -> Self {
+        Self { pos: start, start, end, direction: 1.0, speed }
+    }
+
+    // Gato que va y viene dentro de la celda (`col`, `row`)
+    pub fn in_cell(col: usize, row: usize, block_size: f32) -> Self {
+        let start = Vec2::new(col as f32 + 0.5, row as f32 + 0.5) * block_size;
+        Cat::new(start, start + Vec2::new(0.0, block_size * 0.35), 0.05)
+    }
+
+    pub fn update(&mut self) {
+        // Mover el gato entre las dos posiciones
+        let path = self.end - self.start;
+        let length = path.magnitude();
+        if length > 0.0 {
+            self.pos += path / length * self.speed * self.direction;
+        }
+
+        // Invertir la dirección cuando llega a los extremos
+        let travelled = (self.pos - self.start).dot(&path) / length.max(f32::EPSILON);
+        if travelled >= length || travelled <= 0.0 {
+            self.direction *= -1.0;
+        }
+    }
+}
+
+// Resultado de intentar mover al jugador
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveOutcome {
+    Moved,
+    Blocked,
+    CarrotCollected,
+    HealthPickup,
+    KeyCollected(KeyColor),
+    ReachedGoal,
+}
+
+// Resultado de pulsar la tecla de interactuar
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InteractOutcome {
+    Nothing,
+    DoorOpened { pos: Vec2 }, // Centro de la puerta, de donde sale el sonido
+    Locked(KeyColor), // Falta la llave de ese color
+}
+
+// Resultado de un golpe
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Damage {
+    Hurt,     // Quedan corazones
+    LifeLost, // Se acabaron los corazones: vuelve a la salida con otra vida
+    Dead,     // No quedan vidas
+}
+
+// De dónde sale un nivel: un archivo de la lista de niveles o un laberinto generado
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LevelSource {
+    File(usize),
+    Generated(u64), // Semilla
+}
+
+// Estado inicial de un nivel. No cambia durante la partida, así que sirve
+// para reiniciarlo con las zanahorias en su sitio
+#[derive(Clone)]
+pub struct Level {
+    pub source: LevelSource,
+    pub name: String,
+    pub maze: Vec<Vec<char>>,
+    pub cats: Vec<Cat>,
+}
+
+impl Level {
+    // Cada `c` del archivo es un gato; su celda queda como suelo de tierra
+    pub fn load(maze_file: &str, index: usize) -> Self {
+        let mut maze = load_maze(maze_file);
+        let block_size = block_size_for(&maze) as f32;
+        let mut cats = Vec::new();
+        for (row, line) in maze.iter_mut().enumerate() {
+            for (col, tile) in line.iter_mut().enumerate() {
+                if *tile == CAT_TILE {
+                    *tile = ' ';
+                    cats.push(Cat::in_cell(col, row, block_size));
+                }
+            }
+        }
+        Self { source: LevelSource::File(index), name: maze_file.to_string(), maze, cats }
+    }
+
+    // Laberinto aleatorio reproducible a partir de `seed`
+    pub fn generate(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let cols = rng.gen_range(5..=10);
+        let rows = rng.gen_range(4..=8);
+        let maze = generate_maze(cols, rows, (cols * rows) / 6, 1, &mut rng);
+        let block_size = block_size_for(&maze) as f32;
+
+        // El gato patrulla en una celda lejos de la salida del jugador
+        let x = rng.gen_range(cols / 2..cols);
+        let y = rng.gen_range(rows / 2..rows);
+        let cat = Cat::in_cell(x * 3 + 1, y * 2 + 1, block_size);
+
+        // Después del gato para no cambiar los niveles de semillas anteriores
+        let mut maze = maze;
+        if rng.gen_bool(0.5) {
+            add_locked_door(&mut maze, KeyColor::Red, &mut rng);
+        }
+
+        Self {
+            source: LevelSource::Generated(seed),
+            name: format!("generado-{}", seed),
+            maze,
+            cats: vec![cat],
+        }
+    }
+}
+
+// Tamaño de cada celda para que el laberinto quepa en el mundo
+pub fn block_size_for(maze: &[Vec<char>]) -> usize {
+    let block_size_x = WORLD_WIDTH / maze[0].len();
+    let block_size_y = WORLD_HEIGHT / maze.len();
+    block_size_x.min(block_size_y)
+}
+
+// Estado de una partida en curso dentro de un laberinto
+pub struct World {
+    pub maze: Vec<Vec<char>>,
+    pub block_size: usize,
+    pub player: Player,
+    pub score: u32,
+    pub cats: Vec<Cat>,
+    pub elapsed: f32, // Segundos jugados en este nivel
+    pub health: u32,
+    pub lives: u32,
+    pub invulnerable: f32, // Segundos que quedan sin recibir daño
+    pub hurt_flash: f32,   // Segundos que quedan del destello rojo
+    pub doors: Vec<Door>,  // Puertas que no están abiertas del todo
+    pub keys: Vec<KeyColor>,
+    pub explored: Vec<Vec<bool>>, // Celdas que el jugador ha llegado a ver
+    pub trail: Vec<Vec<Vec2>>, // Recorrido del jugador, en tramos que corta cada salto
+    pub collected: Vec<(usize, usize)>, // Celdas de las zanahorias recogidas, (columna, fila)
+    pub speed: f32, // Velocidad media del jugador en los últimos `SPEED_WINDOW` segundos, en píxeles por segundo
+    pub noclip: bool, // Trucos de la consola: atravesar paredes
+    pub god: bool,    // y no recibir daño
+    last_pos: Vec2,
+    stride: f32, // Distancia andada desde el último paso
+    recent_moves: VecDeque<(f32, f32)>, // Segundos y distancia de cada paso de simulación dentro de la ventana
+}
+
+impl World {
+    // Copia del estado inicial del nivel, lista para jugar
+    pub fn new(level: &Level) -> Self {
+        let maze = level.maze.clone();
+        let block_size = block_size_for(&maze);
+        let doors = find_doors(&maze);
+
+        let player_fov = std::f32::consts::PI / 3.0;
+        let player = Player::new(spawn_position(&maze, block_size), std::f32::consts::PI / 3.0, player_fov);
+        let last_pos = player.pos;
+        let explored = maze.iter().map(|row| vec![false; row.len()]).collect();
+
+        let mut world = Self {
+            maze,
+            block_size,
+            player,
+            score: 0,
+            cats: level.cats.clone(),
+            elapsed: 0.0,
+            health: MAX_HEALTH,
+            lives: START_LIVES,
+            invulnerable: 0.0,
+            hurt_flash: 0.0,
+            doors,
+            keys: Vec::new(),
+            explored,
+            trail: vec![vec![last_pos]],
+            collected: Vec::new(),
+            speed: 0.0,
+            noclip: false,
+            god: false,
+            last_pos,
+            stride: 0.0,
+            recent_moves: VecDeque::new(),
+        };
+        world.reveal();
+        world
+    }
+
+    pub fn cell_at(&self, pos: Vec2) -> char {
+        check_collision(pos, &self.maze, self.block_size)
+    }
+
+    pub fn move_forward(&mut self) -> MoveOutcome {
+        let dir = Vec2::new(self.player.a.cos(), self.player.a.sin());
+        self.try_move(self.player.pos + dir * MOVE_STEP)
+    }
+
+    pub fn move_backward(&mut self) -> MoveOutcome {
+        let dir = Vec2::new(self.player.a.cos(), self.player.a.sin());
+        self.try_move(self.player.pos - dir * MOVE_STEP)
+    }
+
+    pub fn turn_left(&mut self) {
+        self.player.a -= TURN_STEP;
+    }
+
+    pub fn turn_right(&mut self) {
+        self.player.a += TURN_STEP;
+    }
+
+    pub fn try_move(&mut self, new_pos: Vec2) -> MoveOutcome {
+        // Check if the player reached the goal 'g'
+        match self.cell_at(new_pos) {
+            'g' => MoveOutcome::ReachedGoal,
+            tile if is_floor(tile) => {
+                self.player.pos = new_pos;
+                MoveOutcome::Moved
+            }
+            'z' => {
+                self.player.pos = new_pos;
+                self.score += 1;
+                let cell = (new_pos.x as usize / self.block_size, new_pos.y as usize / self.block_size);
+                self.maze[cell.1][cell.0] = ' ';
+                self.collected.push(cell);
+                MoveOutcome::CarrotCollected
+            }
+            'h' => {
+                self.player.pos = new_pos;
+                self.health = (self.health + 1).min(MAX_HEALTH);
+                self.maze[new_pos.y as usize / self.block_size][new_pos.x as usize / self.block_size] = ' ';
+                MoveOutcome::HealthPickup
+            }
+            tile => match KeyColor::from_key_tile(tile) {
+                Some(color) => {
+                    self.player.pos = new_pos;
+                    if !self.keys.contains(&color) {
+                        self.keys.push(color);
+                    }
+                    self.maze[new_pos.y as usize / self.block_size][new_pos.x as usize / self.block_size] = ' ';
+                    MoveOutcome::KeyCollected(color)
+                }
+                // Sin colisiones solo frena el borde del laberinto
+                None if self.noclip && self.inside(new_pos) => {
+                    self.player.pos = new_pos;
+                    MoveOutcome::Moved
+                }
+                None => MoveOutcome::Blocked,
+            },
+        }
+    }
+
+    // `pos` cae dentro del laberinto y no en su borde exterior
+    pub fn inside(&self, pos: Vec2) -> bool {
+        let bs = self.block_size as f32;
+        let (cols, rows) = (self.maze[0].len() as f32, self.maze.len() as f32);
+        pos.x >= bs && pos.y >= bs && pos.x < (cols - 1.0) * bs && pos.y < (rows - 1.0) * bs
+    }
+
+    // Abre las puertas al alcance que estén delante del jugador
+    pub fn interact(&mut self) -> InteractOutcome {
+        let dir = Vec2::new(self.player.a.cos(), self.player.a.sin());
+        let reach = INTERACT_BLOCKS * self.block_size as f32;
+        let mut outcome = InteractOutcome::Nothing;
+        for door in &mut self.doors {
+            let to_door = door.center(self.block_size) - self.player.pos;
+            if door.opening || to_door.magnitude() > reach || to_door.dot(&dir) <= 0.0 {
+                continue;
+            }
+            match door.lock {
+                Some(color) if !self.keys.contains(&color) => {
+                    if outcome == InteractOutcome::Nothing {
+                        outcome = InteractOutcome::Locked(color);
+                    }
+                }
+                _ => {
+                    door.opening = true;
+                    if !matches!(outcome, InteractOutcome::DoorOpened { .. }) {
+                        outcome = InteractOutcome::DoorOpened { pos: door.center(self.block_size) };
+                    }
+                }
+            }
+        }
+        outcome
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed += dt;
+        self.invulnerable = (self.invulnerable - dt).max(0.0);
+        self.hurt_flash = (self.hurt_flash - dt).max(0.0);
+
+        // Las puertas abiertas del todo dejan de serlo y su celda queda libre
+        for door in self.doors.iter_mut().filter(|d| d.opening) {
+            door.open = (door.open + dt / DOOR_OPEN_TIME).min(1.0);
+            if door.open >= 1.0 {
+                self.maze[door.row][door.col] = ' ';
+            }
+        }
+        self.doors.retain(|d| d.open < 1.0);
+        for cat in &mut self.cats {
+            cat.update();
+        }
+        self.reveal();
+        self.record_trail();
+    }
+
+    // Añade la posición al recorrido si se ha alejado lo bastante del último punto
+    fn record_trail(&mut self) {
+        let spacing = TRAIL_SPACING_BLOCKS * self.block_size as f32;
+        let pos = self.player.pos;
+        match self.trail.last_mut() {
+            Some(segment) => match segment.last() {
+                Some(last) if (pos - last).magnitude() < spacing => {}
+                _ => segment.push(pos),
+            },
+            None => self.trail.push(vec![pos]),
+        }
+    }
+
+    // Marca como vistas las celdas que cruzan los rayos del campo de visión,
+    // hasta la pared (incluida) en la que acaba cada uno
+    pub fn reveal(&mut self) {
+        let bs = self.block_size as f32;
+        let step = bs / 4.0;
+        for i in 0..VISION_RAYS {
+            let a = self.player.a - self.player.fov / 2.0 + self.player.fov * i as f32 / (VISION_RAYS - 1) as f32;
+            let dir = Vec2::new(a.cos(), a.sin());
+            let distance = trace_ray(&self.maze, &self.doors, &self.player, a, self.block_size).distance + 1.0;
+            let mut t: f32 = 0.0;
+            loop {
+                let pos = self.player.pos + dir * t.min(distance);
+                let (col, row) = ((pos.x / bs) as usize, (pos.y / bs) as usize);
+                if let Some(cell) = self.explored.get_mut(row).and_then(|line| line.get_mut(col)) {
+                    *cell = true;
+                }
+                if t >= distance {
+                    break;
+                }
+                t += step;
+            }
+        }
+    }
+
+    // Pone al jugador en `pos` sin que cuente como andar; el recorrido sigue
+    // en un tramo nuevo
+    pub fn teleport(&mut self, pos: Vec2) {
+        self.player.pos = pos;
+        self.last_pos = pos;
+        self.trail.push(vec![pos]);
+    }
+
+    // Mide lo que anduvo el jugador en el paso de `dt` segundos que acaba de
+    // pasar y devuelve el suelo que pisa si le toca dar un paso
+    pub fn footstep(&mut self, dt: f32) -> Option<Floor> {
+        let moved = (self.player.pos - self.last_pos).magnitude();
+        self.last_pos = self.player.pos;
+        let was_walking = self.speed > 0.0;
+        self.recent_moves.push_back((dt, moved));
+        while self.recent_moves.iter().map(|(t, _)| t).sum::<f32>() > SPEED_WINDOW && self.recent_moves.len() > 1 {
+            self.recent_moves.pop_front();
+        }
+        let (time, distance) = self.recent_moves.iter().fold((0.0, 0.0), |(t, d), &(dt, m)| (t + dt, d + m));
+        self.speed = distance / time.max(f32::EPSILON);
+        if moved <= 0.0 {
+            return None;
+        }
+
+        let stride = STRIDE_BLOCKS * self.block_size as f32;
+        // Al echar a andar el primer paso suena enseguida
+        if !was_walking {
+            self.stride = stride;
+        }
+        self.stride += moved;
+        if self.stride < stride {
+            return None;
+        }
+        self.stride %= stride;
+        Floor::from_tile(self.cell_at(self.player.pos))
+    }
+
+    // Gatos que maúllan en el paso de `dt` segundos que acaba de pasar
+    pub fn meowing_cats(&self, dt: f32) -> Vec<Vec2> {
+        self.cats
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                let t = self.elapsed + *i as f32 * CAT_MEOW_STAGGER;
+                (t / CAT_MEOW_INTERVAL).floor() > ((t - dt) / CAT_MEOW_INTERVAL).floor()
+            })
+            .map(|(_, cat)| cat.pos)
+            .collect()
+    }
+
+    // Verificar colisión con el gato: si toca al jugador y este no es
+    // invulnerable, le quita vida
+    pub fn check_cat_contact(&mut self) -> Option<Damage> {
+        if self.invulnerable > 0.0 || self.god {
+            return None;
+        }
+        let cat = self
+            .cats
+            .iter()
+            .find(|cat| (self.player.pos - cat.pos).magnitude() < self.block_size as f32)?
+            .pos;
+        Some(self.take_damage(CAT_DAMAGE, cat))
+    }
+
+    // `from`: de dónde viene el golpe, para empujar al jugador en sentido contrario
+    pub fn take_damage(&mut self, amount: u32, from: Vec2) -> Damage {
+        self.health = self.health.saturating_sub(amount);
+        self.invulnerable = INVULNERABLE_TIME;
+        self.hurt_flash = HURT_FLASH_TIME;
+        if self.health > 0 {
+            self.knockback(from);
+            return Damage::Hurt;
+        }
+
+        self.lives = self.lives.saturating_sub(1);
+        if self.lives == 0 {
+            return Damage::Dead;
+        }
+        self.health = MAX_HEALTH;
+        self.teleport(spawn_position(&self.maze, self.block_size));
+        Damage::LifeLost
+    }
+
+    // Aleja al jugador de `from` a pasos cortos hasta chocar con algo
+    fn knockback(&mut self, from: Vec2) {
+        let away = self.player.pos - from;
+        let dir = if away.magnitude() > f32::EPSILON {
+            away.normalize()
+        } else {
+            -Vec2::new(self.player.a.cos(), self.player.a.sin())
+        };
+        let distance = KNOCKBACK_BLOCKS * self.block_size as f32;
+        let steps = (distance / 2.0) as usize;
+        for _ in 0..steps {
+            let next = self.player.pos + dir * 2.0;
+            if !is_floor(self.cell_at(next)) {
+                break;
+            }
+            self.player.pos = next;
+        }
+        self.last_pos = self.player.pos;
+    }
+}
+
+// Centro de la celda libre a la derecha de 'p'
+fn spawn_position(maze: &[Vec<char>], block_size: usize) -> Vec2 {
+    let (col, row) = crate::maze::find_cell(maze, 'p').map(|(c, r)| (c + 1, r)).unwrap_or((1, 1));
+    Vec2::new(
+        (col as f32 + 0.5) * block_size as f32,
+        (row as f32 + 0.5) * block_size as f32,
+    )
+}
+
+pub fn check_collision(pos: Vec2, maze: &[Vec<char>], block_size: usize) -> char {
+    let i = pos.x as usize / block_size;
+    let j = pos.y as usize / block_size;
+    maze[j][i]
+}
@@ -0,0 +1,144 @@
+// Sonidos que dispara una partida, con `AudioManager::recording()` en vez de
+// un dispositivo de audio
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use nalgebra_glm::Vec2;
+use raycaster::audio::{
+    AudioCommand, AudioManager, Bus, LEVEL_MUSIC, MENU_MUSIC, SFX_CARROT, SFX_HURT, STEP_DIRT, STEP_GRASS, STEP_STONE,
+    WIN_MUSIC,
+};
+use raycaster::game::{Game, GameState, View, TICK_DT};
+use raycaster::input::{Action, Input};
+use raycaster::settings::Settings;
+
+type Log = Rc<RefCell<Vec<AudioCommand>>>;
+
+fn start() -> (Game, AudioManager, Log) {
+    let mut game = Game::new(vec!["maze.txt".to_string()], 0, Settings::default());
+    game.transition(GameState::Playing { view: View::TwoD });
+    game.drain_events();
+    let (audio, log) = AudioManager::recording();
+    (game, audio, log)
+}
+
+// Un tick con el audio al día, como en la ventana y en `simulate`
+fn step(game: &mut Game, audio: &mut AudioManager) {
+    game.tick();
+    audio.update(game, TICK_DT);
+    for event in game.drain_events() {
+        audio.handle_event(&event, &game.world);
+    }
+}
+
+fn press(game: &mut Game, action: Action) {
+    game.apply(Input::Key { action, pressed: true });
+    game.apply(Input::Key { action, pressed: false });
+}
+
+// Efectos sueltos que sonaron en `bus`
+fn played(log: &Log, bus: Bus) -> Vec<String> {
+    log.borrow()
+        .iter()
+        .filter_map(|command| match command {
+            AudioCommand::PlayOnce { bus: b, sound, .. } | AudioCommand::PlayAt { bus: b, sound, .. } if *b == bus => {
+                Some(sound.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// Pistas a las que fundió la música, en orden
+fn crossfades(log: &Log) -> Vec<Option<String>> {
+    log.borrow()
+        .iter()
+        .filter_map(|command| match command {
+            AudioCommand::Crossfade { bus: Bus::Music, sound, .. } => Some(sound.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+// Centro de la celda (columna, fila)
+fn cell_center(game: &Game, col: usize, row: usize) -> Vec2 {
+    let bs = game.world.block_size as f32;
+    Vec2::new((col as f32 + 0.5) * bs, (row as f32 + 0.5) * bs)
+}
+
+#[test]
+fn carrot_pickup_plays_its_sound() {
+    let (mut game, mut audio, log) = start();
+    // Encima de la zanahoria de la columna 1, fila 7, mirando hacia ella
+    game.world.player.pos = cell_center(&game, 1, 6);
+    game.world.player.a = std::f32::consts::FRAC_PI_2;
+    for _ in 0..20 {
+        press(&mut game, Action::Forward);
+        step(&mut game, &mut audio);
+    }
+    assert_eq!(game.world.score, 1);
+    assert_eq!(played(&log, Bus::Sfx), [SFX_CARROT]);
+}
+
+#[test]
+fn cat_hit_plays_once_while_invulnerable() {
+    let (mut game, mut audio, log) = start();
+    game.run_command("spawn");
+    let player = game.world.player.pos;
+    for _ in 0..10 {
+        game.world.cats[0].pos = player;
+        step(&mut game, &mut audio);
+    }
+    assert!(game.world.health < raycaster::world::MAX_HEALTH);
+    assert_eq!(played(&log, Bus::Sfx).iter().filter(|s| *s == SFX_HURT).count(), 1);
+}
+
+#[test]
+fn walking_plays_footsteps_and_standing_still_does_not() {
+    let (mut game, mut audio, log) = start();
+    for _ in 0..30 {
+        step(&mut game, &mut audio);
+    }
+    assert!(played(&log, Bus::Footsteps).is_empty());
+
+    // Hacia el sur desde la salida: cuatro celdas libres
+    game.world.player.a = std::f32::consts::FRAC_PI_2;
+    for _ in 0..8 {
+        press(&mut game, Action::Forward);
+        step(&mut game, &mut audio);
+    }
+    let steps = played(&log, Bus::Footsteps);
+    assert!(!steps.is_empty(), "al andar suenan pasos");
+    assert!(steps.iter().all(|s| [STEP_DIRT, STEP_STONE, STEP_GRASS].contains(&s.as_str())));
+}
+
+// Con la tecla pulsada a saltos, como antes de que el sistema la repita, los
+// pasos siguen la distancia y no suena uno en cada pulsación
+#[test]
+fn footsteps_follow_distance_between_key_repeats() {
+    let (mut game, mut audio, log) = start();
+    game.world.player.a = std::f32::consts::FRAC_PI_2;
+    for _ in 0..8 {
+        press(&mut game, Action::Forward);
+        for _ in 0..20 {
+            step(&mut game, &mut audio);
+            assert!(game.world.speed > 0.0);
+        }
+    }
+    let walked = 8.0 * 10.0 / game.world.block_size as f32;
+    let steps = played(&log, Bus::Footsteps).len();
+    assert!(steps >= 2 && steps as f32 <= 1.0 + walked / 0.45, "{} pasos en {} bloques", steps, walked);
+}
+
+#[test]
+fn music_changes_track_with_the_state() {
+    let mut game = Game::new(vec!["maze.txt".to_string()], 0, Settings::default());
+    let (mut audio, log) = AudioManager::recording();
+    step(&mut game, &mut audio);
+    game.transition(GameState::Playing { view: View::TwoD });
+    step(&mut game, &mut audio);
+    game.transition(GameState::Won);
+    step(&mut game, &mut audio);
+    let expected = [MENU_MUSIC, LEVEL_MUSIC[0], WIN_MUSIC].map(|track| Some(track.to_string()));
+    assert_eq!(crossfades(&log), expected);
+}
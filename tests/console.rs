@@ -0,0 +1,170 @@
+// Consola de desarrollo: lectura de órdenes, lo que hacen, el autocompletado,
+// el historial y las órdenes de arranque tecleadas con `apply`
+use raycaster::console::{parse_command, Command, Console, ConsoleError};
+use raycaster::game::{Game, GameState, View};
+use raycaster::input::{Action, Input};
+use raycaster::settings::{Settings, FOV_MAX_DEGREES};
+use raycaster::storage::MemoryStorage;
+
+fn start() -> Game {
+    let mut game = Game::new(vec!["maze.txt".to_string()], 0, Settings::default());
+    game.transition(GameState::Playing { view: View::TwoD });
+    game
+}
+
+fn last_output(game: &Game) -> &str {
+    game.console().output.last().map_or("", String::as_str)
+}
+
+fn type_line(console: &mut Console, line: &str) {
+    for c in line.chars() {
+        console.type_char(c);
+    }
+}
+
+#[test]
+fn parses_commands_and_arguments() {
+    assert_eq!(parse_command("tp 3 4"), Ok(Some(Command::Teleport { col: 3, row: 4 })));
+    assert_eq!(parse_command("  fov   90 "), Ok(Some(Command::Fov(90.0))));
+    assert_eq!(parse_command("spawn"), Ok(Some(Command::Spawn(None))));
+    assert_eq!(parse_command("spawn 2 5"), Ok(Some(Command::Spawn(Some((2, 5))))));
+    assert_eq!(
+        parse_command("exec ~/dev.cfg"),
+        Ok(Some(Command::Exec("~/dev.cfg".to_string())))
+    );
+    assert_eq!(parse_command(""), Ok(None));
+    assert_eq!(parse_command("# comentario"), Ok(None));
+}
+
+#[test]
+fn unknown_commands_and_bad_arguments() {
+    assert_eq!(parse_command("volar"), Err(ConsoleError::Unknown("volar".to_string())));
+    assert_eq!(parse_command("tp 3"), Err(ConsoleError::Usage("tp", "columna fila")));
+    assert_eq!(parse_command("score muchos"), Err(ConsoleError::Usage("score", "n")));
+    assert_eq!(parse_command("noclip ya"), Err(ConsoleError::Usage("noclip", "")));
+
+    // En la partida el error se escribe en la consola y no cambia nada
+    let mut game = start();
+    game.run_command("volar alto");
+    assert_eq!(last_output(&game), ConsoleError::Unknown("volar".to_string()).to_string());
+    game.run_command("score -3");
+    assert_eq!(last_output(&game), "Uso: score n");
+    assert_eq!(game.world.score, 0);
+}
+
+#[test]
+fn execute_changes_the_game() {
+    let mut game = start();
+    game.run_command("score 5");
+    assert_eq!(game.world.score, 5);
+    assert_eq!(last_output(&game), "Puntos: 5");
+
+    game.run_command("noclip");
+    assert!(game.world.noclip);
+    game.run_command("god");
+    game.run_command("god");
+    assert!(!game.world.god);
+
+    // El campo de visión se queda en lo que permite el menú
+    game.run_command("fov 500");
+    assert_eq!(game.settings.fov_degrees, FOV_MAX_DEGREES);
+
+    game.run_command("set lives 7");
+    assert_eq!(game.world.lives, 7);
+
+    game.run_command("clear");
+    assert!(game.console().output.is_empty());
+}
+
+#[test]
+fn tab_completes_commands_and_keys() {
+    let mut console = Console::default();
+    type_line(&mut console, "sp\t");
+    assert_eq!(console.input, "spawn ");
+
+    // Varias opciones: se completa lo común y se listan
+    let mut console = Console::default();
+    type_line(&mut console, "s\t");
+    assert_eq!(console.input, "s");
+    assert_eq!(console.output, ["score  spawn  set"]);
+
+    let mut console = Console::default();
+    type_line(&mut console, "set mus\t");
+    assert_eq!(console.input, "set music_volume ");
+    let mut console = Console::default();
+    type_line(&mut console, "set li\t");
+    assert_eq!(console.input, "set lives ");
+
+    // Sin nada que completar no cambia
+    let mut console = Console::default();
+    type_line(&mut console, "tp 1\t");
+    assert_eq!(console.input, "tp 1");
+}
+
+#[test]
+fn history_walks_previous_commands() {
+    let mut console = Console::default();
+    for line in ["score 1", "score 1", "god", "  "] {
+        type_line(&mut console, line);
+        console.submit();
+    }
+    // Sin repetir la misma orden seguida ni guardar líneas vacías
+    console.history_previous();
+    assert_eq!(console.input, "god");
+    console.history_previous();
+    assert_eq!(console.input, "score 1");
+    console.history_previous();
+    assert_eq!(console.input, "score 1");
+    console.history_next();
+    assert_eq!(console.input, "god");
+    console.history_next();
+    assert_eq!(console.input, "");
+}
+
+#[test]
+fn startup_script_is_typed_through_apply() {
+    let mut storage = MemoryStorage::default();
+    storage.files.insert("autoexec.cfg".to_string(), "score 2\nexec ~/dev.cfg\n".to_string());
+    storage.files.insert("~/dev.cfg".to_string(), "score 3\n".to_string());
+    let mut game = start();
+    game.set_storage(Box::new(storage));
+    game.start_recording();
+    game.run_startup_script("autoexec.cfg").unwrap();
+    assert!(!game.console_open());
+    assert_eq!(game.world.score, 5);
+
+    // Lo grabado son teclas y caracteres, como si los hubiera escrito alguien
+    let replay = game.finish_recording().unwrap();
+    let typed: String = replay
+        .inputs
+        .iter()
+        .filter_map(|(_, input)| match input {
+            Input::Text(c) => Some(*c),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(typed, "score 2\nexec ~/dev.cfg\n");
+    assert!(replay.inputs.iter().any(|(_, input)| *input == Input::Key { action: Action::Console, pressed: true }));
+}
+
+// El carácter de la tecla de la consola no se escribe, pero el mismo carácter
+// tecleado después sí
+#[test]
+fn console_key_character_is_skipped_once() {
+    let mut game = start();
+    game.apply(Input::Key { action: Action::Console, pressed: true });
+    game.apply(Input::Text('`'));
+    game.apply(Input::Key { action: Action::Console, pressed: false });
+    for c in "~`º".chars() {
+        game.apply(Input::Text(c));
+    }
+    assert_eq!(game.console().input, "~`º");
+
+    // Si la tecla no da carácter, al soltarla se deja de esperar
+    game.apply(Input::Key { action: Action::Console, pressed: true });
+    game.apply(Input::Key { action: Action::Console, pressed: false });
+    game.apply(Input::Key { action: Action::Console, pressed: true });
+    game.apply(Input::Key { action: Action::Console, pressed: false });
+    game.apply(Input::Text('x'));
+    assert!(game.console().input.ends_with('x'));
+}
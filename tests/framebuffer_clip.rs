@@ -0,0 +1,56 @@
+// Las primitivas recortan a la pantalla sin desbordarse
+use raycaster::framebuffer::Framebuffer;
+
+const RED: [u8; 4] = [0xFF, 0x00, 0x00, 0xFF];
+
+#[test]
+fn zero_sized_framebuffer() {
+    let mut buffer = Vec::new();
+    let mut framebuffer = Framebuffer::new(0, 0, &mut buffer);
+    framebuffer.hline(-5, 5, 0, RED);
+    framebuffer.vline(0, -5, 5, RED);
+    framebuffer.fill_rect(-2, -2, 10, 10, RED);
+    framebuffer.blend_rect(-2, -2, 10, 10, RED, 0.5);
+}
+
+#[test]
+fn rects_are_clipped() {
+    let (width, height) = (8, 6);
+    let mut buffer = vec![0u8; width * height * 4];
+    let mut framebuffer = Framebuffer::new(width, height, &mut buffer);
+    // Fuera de la pantalla por cada lado: no pinta nada
+    framebuffer.fill_rect(-100, 0, 50, 3, RED);
+    framebuffer.fill_rect(0, 1_000_000, 4, 1_000_000, RED);
+    framebuffer.fill_rect(8, 0, 4, 4, RED);
+    framebuffer.hline(-3, 20, -1, RED);
+    assert!(buffer.iter().all(|&b| b == 0));
+
+    // Un rectángulo que se sale por la esquina pinta solo lo que cabe
+    let mut framebuffer = Framebuffer::new(width, height, &mut buffer);
+    framebuffer.fill_rect(6, 4, 10, 10, RED);
+    let painted: Vec<(usize, usize)> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| buffer[(y * width + x) * 4] == 0xFF)
+        .collect();
+    assert_eq!(painted, vec![(6, 4), (7, 4), (6, 5), (7, 5)]);
+}
+
+// Una línea larguísima que casi toda cae fuera pinta solo el tramo visible, y
+// sin recorrer lo demás píxel a píxel
+#[test]
+fn long_lines_are_clipped() {
+    let (width, height) = (8, 6);
+    let mut buffer = vec![0u8; width * height * 4];
+    let mut framebuffer = Framebuffer::new(width, height, &mut buffer);
+    let far = 1_000_000_000;
+    framebuffer.draw_line(-far, -far, far, far, RED);
+    framebuffer.draw_line_aa(-1.0e9, 3.0, 1.0e9, 3.0, RED);
+    // Enteramente fuera, por cada lado
+    framebuffer.draw_line(-far, -1, far, -far, RED);
+    framebuffer.draw_line_aa(20.0, -1.0e9, 30.0, 1.0e9, RED);
+    let painted = |x: usize, y: usize| buffer[(y * width + x) * 4] == 0xFF;
+    let diagonal: Vec<usize> = (0..height).filter(|&i| painted(i, i)).collect();
+    assert_eq!(diagonal, (0..height).collect::<Vec<_>>());
+    assert!((0..width).all(|x| painted(x, 3)));
+    assert!((0..height).flat_map(|y| (0..width).map(move |x| (x, y))).all(|(x, y)| x == y || y == 3 || !painted(x, y)));
+}
@@ -0,0 +1,17 @@
+// Los gatos de cada nivel salen de las `c` de su archivo
+use raycaster::maze::is_floor;
+use raycaster::world::{block_size_for, Level};
+
+#[test]
+fn cats_start_on_their_markers() {
+    for (file, cells) in [("maze.txt", [(4, 1)]), ("maze2.txt", [(22, 11)])] {
+        let level = Level::load(file, 0);
+        let bs = block_size_for(&level.maze) as f32;
+        let starts: Vec<(usize, usize)> =
+            level.cats.iter().map(|cat| ((cat.start.x / bs) as usize, (cat.start.y / bs) as usize)).collect();
+        assert_eq!(starts, cells, "{}", file);
+        for (col, row) in cells {
+            assert!(is_floor(level.maze[row][col]), "{}: la celda del gato queda como suelo", file);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+// Nombre de un récord: `Enter` llega como tecla y como carácter, en cualquier
+// orden, y solo la tecla lo guarda
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+use raycaster::game::{Game, GameState, View};
+use raycaster::input::{Action, Input};
+use raycaster::leaderboard::Leaderboard;
+use raycaster::settings::Settings;
+use raycaster::storage::{MemoryStorage, Storage};
+
+// Almacenamiento que el test puede mirar después de dárselo al juego
+#[derive(Clone, Default)]
+struct Shared(Rc<RefCell<MemoryStorage>>);
+
+impl Storage for Shared {
+    fn read(&mut self, key: &str) -> io::Result<String> {
+        self.0.borrow_mut().read(key)
+    }
+
+    fn write(&mut self, key: &str, text: &str) -> io::Result<()> {
+        self.0.borrow_mut().write(key, text)
+    }
+}
+
+fn won() -> (Game, Shared) {
+    let storage = Shared::default();
+    let mut game = Game::new(vec!["maze.txt".to_string()], 0, Settings::default());
+    game.set_storage(Box::new(storage.clone()));
+    game.transition(GameState::Playing { view: View::TwoD });
+    for _ in 0..5 {
+        game.tick();
+    }
+    game.transition(GameState::Won);
+    assert!(game.entering_name());
+    (game, storage)
+}
+
+fn names(game: &Game, storage: &mut Shared) -> Vec<String> {
+    Leaderboard::load(storage, game.run.key).entries.into_iter().map(|e| e.name).collect()
+}
+
+fn confirm(game: &mut Game) {
+    game.apply(Input::Key { action: Action::Confirm, pressed: true });
+    game.apply(Input::Key { action: Action::Confirm, pressed: false });
+}
+
+#[test]
+fn enter_character_before_the_key() {
+    let (mut game, mut storage) = won();
+    for c in "Ana Pi\r".chars() {
+        game.apply(Input::Text(c));
+    }
+    assert!(game.entering_name(), "el carácter no guarda");
+    confirm(&mut game);
+    assert!(!game.entering_name());
+    assert_eq!(names(&game, &mut storage), ["Ana Pi"]);
+}
+
+#[test]
+fn enter_key_before_the_character() {
+    let (mut game, mut storage) = won();
+    for c in "Ana".chars() {
+        game.apply(Input::Text(c));
+    }
+    confirm(&mut game);
+    game.apply(Input::Text('\r'));
+    assert_eq!(names(&game, &mut storage), ["Ana"]);
+}
+
+#[test]
+fn control_characters_are_not_typed() {
+    let (mut game, mut storage) = won();
+    for c in "A\u{1b}\tn\u{0}a\u{8}o".chars() {
+        game.apply(Input::Text(c));
+    }
+    confirm(&mut game);
+    assert_eq!(names(&game, &mut storage), ["Ano"]);
+}
@@ -0,0 +1,52 @@
+// La imagen no puede depender del número de hilos del render
+use nalgebra_glm::Vec2;
+
+use raycaster::door::find_doors;
+use raycaster::framebuffer::Framebuffer;
+use raycaster::maze::{find_cell, load_maze};
+use raycaster::player::Player;
+use raycaster::render::{render2d, render3d, set_render_threads, Camera2d, VIEW2D_BLOCKS};
+
+const WIDTH: usize = 320;
+const HEIGHT: usize = 200;
+
+// Fotogramas 3D (con y sin niebla) y 2D desde la primera celda libre de cada
+// laberinto, mirando en varias direcciones
+fn render_frames() -> Vec<Vec<u8>> {
+    let mut frames = Vec::new();
+    for maze_file in ["maze.txt", "maze2.txt"] {
+        let maze = load_maze(maze_file);
+        let doors = find_doors(&maze);
+        let block_size = (WIDTH / maze[0].len()).min(HEIGHT / maze.len());
+        let (col, row) = find_cell(&maze, ' ').expect("El laberinto no tiene celdas libres");
+        let pos = Vec2::new((col as f32 + 0.5) * block_size as f32, (row as f32 + 0.5) * block_size as f32);
+        for a in [0.0, 0.7, 2.0, 4.1] {
+            let player = Player::new(pos, a, std::f32::consts::PI / 3.0);
+            for fog in [false, true] {
+                let mut buffer = vec![0u8; WIDTH * HEIGHT * 4];
+                render3d(&mut Framebuffer::new(WIDTH, HEIGHT, &mut buffer), &player, block_size, &maze, &doors, fog);
+                frames.push(buffer);
+            }
+            let mut buffer = vec![0u8; WIDTH * HEIGHT * 4];
+            let camera = Camera2d::follow(player.pos, &maze, block_size, VIEW2D_BLOCKS, WIDTH, HEIGHT);
+            render2d(&mut Framebuffer::new(WIDTH, HEIGHT, &mut buffer), &player, &maze, &doors, &camera, block_size);
+            frames.push(buffer);
+        }
+    }
+    frames
+}
+
+// Un solo test: el pool es global y otro test en paralelo podría cambiarlo
+#[test]
+fn same_image_with_any_thread_count() {
+    set_render_threads(1).unwrap();
+    let single = render_frames();
+    for threads in [2, 4, 7] {
+        set_render_threads(threads).unwrap();
+        let frames = render_frames();
+        assert_eq!(frames.len(), single.len());
+        for (i, (a, b)) in single.iter().zip(&frames).enumerate() {
+            assert!(a == b, "el fotograma {} cambia con {} hilos", i, threads);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+// Lo que la partida grabada leyó de fuera (partidas guardadas, órdenes de
+// arranque) viaja con la repetición
+use raycaster::game::{Game, GameState, View};
+use raycaster::input::{Action, Input};
+use raycaster::replay::{Replay, ReplayCheck};
+use raycaster::save::{write_slot, QUICK_SLOT};
+use raycaster::settings::Settings;
+use raycaster::storage::MemoryStorage;
+
+fn levels() -> Vec<String> {
+    vec!["maze.txt".to_string()]
+}
+
+fn press(game: &mut Game, action: Action) {
+    game.apply(Input::Key { action, pressed: true });
+    game.apply(Input::Key { action, pressed: false });
+}
+
+#[test]
+fn loaded_save_travels_with_the_replay() {
+    // Partida guardada un poco más adelante que el principio
+    let mut other = Game::new(levels(), 7, Settings::default());
+    other.transition(GameState::Playing { view: View::ThreeD });
+    press(&mut other, Action::TurnRight);
+    press(&mut other, Action::Forward);
+    other.tick();
+    let save = other.snapshot().expect("se está jugando");
+    let mut storage = MemoryStorage::default();
+    write_slot(&mut storage, &save, QUICK_SLOT).unwrap();
+
+    let mut game = Game::new(levels(), 7, Settings::default());
+    game.set_storage(Box::new(storage));
+    game.start_recording();
+    press(&mut game, Action::Confirm);
+    game.tick();
+    press(&mut game, Action::QuickLoad);
+    for _ in 0..30 {
+        game.tick();
+    }
+    assert_eq!(game.world.player.pos, save.player_pos, "se cargó la partida");
+    let recorded = game.finish_recording().unwrap();
+    assert_eq!(recorded.files.len(), 1);
+
+    let replay = Replay::parse(&recorded.to_text()).unwrap();
+    assert_eq!(replay, recorded);
+    let replayed = replay.play(|_| {});
+    let expected = replay.check.as_ref().unwrap();
+    assert!(expected.matches(&ReplayCheck::of(&replayed)), "{}\n{}", expected, ReplayCheck::of(&replayed));
+}
+
+// Las órdenes de arranque se graban como si se tecleasen en la consola
+#[test]
+fn startup_script_is_recorded() {
+    let mut storage = MemoryStorage::default();
+    storage.files.insert("autoexec.cfg".to_string(), "# arranque\nscore 5\nset lives 7\n".to_string());
+    let mut game = Game::new(levels(), 3, Settings::default());
+    game.set_storage(Box::new(storage));
+    game.start_recording();
+    game.run_startup_script("autoexec.cfg").unwrap();
+    assert!(!game.console_open());
+    press(&mut game, Action::Confirm);
+    game.tick();
+    assert_eq!((game.world.score, game.world.lives), (5, 7));
+
+    let recorded = game.finish_recording().unwrap();
+    assert!(recorded.files.is_empty());
+    let replayed = Replay::parse(&recorded.to_text()).unwrap().play(|_| {});
+    assert!(recorded.check.unwrap().matches(&ReplayCheck::of(&replayed)));
+}
@@ -0,0 +1,131 @@
+// Formato de las partidas guardadas: lo que se escribe se vuelve a leer igual
+// y lo que no se puede cargar da un error que dice por qué
+use raycaster::game::{Game, GameState, View};
+use raycaster::input::{Action, Input};
+use raycaster::save::{SaveError, SaveGame, SavedLevel, SAVE_VERSION};
+use raycaster::settings::Settings;
+
+fn press(game: &mut Game, action: Action) {
+    game.apply(Input::Key { action, pressed: true });
+    game.apply(Input::Key { action, pressed: false });
+}
+
+// Partida en `level`, un poco después de empezar
+fn saved_on(level: &str) -> (Game, SaveGame) {
+    let mut game = Game::new(vec![level.to_string()], 5, Settings::default());
+    game.transition(GameState::Playing { view: View::ThreeD });
+    press(&mut game, Action::TurnRight);
+    press(&mut game, Action::Forward);
+    for _ in 0..10 {
+        game.tick();
+    }
+    let save = game.snapshot().expect("se está jugando");
+    (game, save)
+}
+
+// `text` con la línea que empieza por `key` cambiada por `line`
+fn replace_line(text: &str, key: &str, line: &str) -> String {
+    text.lines().map(|l| if l.starts_with(key) { line } else { l }).collect::<Vec<_>>().join("\n")
+}
+
+#[test]
+fn text_round_trip() {
+    let (_, save) = saved_on("maze.txt");
+    assert!(!save.cats.is_empty() && !save.carrots.is_empty());
+    assert_eq!(SaveGame::parse(&save.to_text()).unwrap(), save);
+}
+
+#[test]
+fn versions_out_of_range() {
+    let (_, save) = saved_on("maze.txt");
+    let text = save.to_text();
+    let (header, body) = text.split_once('\n').unwrap();
+    let name = header.split_whitespace().next().unwrap();
+    let with_version = |version: u32| format!("{} {}\n{}", name, version, body);
+    let newer = SAVE_VERSION + 1;
+    assert!(matches!(SaveGame::parse(&with_version(newer)), Err(SaveError::TooNew { version }) if version == newer));
+    assert!(matches!(SaveGame::parse(&with_version(0)), Err(SaveError::TooOld { version: 0 })));
+    assert!(matches!(SaveGame::parse("no es una partida"), Err(SaveError::NotASave)));
+}
+
+#[test]
+fn corrupt_lines_are_numbered() {
+    let (_, save) = saved_on("maze.txt");
+    let text = save.to_text();
+    let line_of = |key: &str| text.lines().position(|l| l.starts_with(key)).unwrap() + 1;
+    let cases = [
+        ("view", "view = 4d"),
+        ("score", "score = mucho"),
+        ("cat", "cat = 1 2"),
+        ("health", "health = 3"),
+        ("level", "sin signo igual"),
+    ];
+    for (key, line) in cases {
+        match SaveGame::parse(&replace_line(&text, key, line)) {
+            Err(SaveError::Corrupt { line: number, .. }) => assert_eq!(number, line_of(key), "{}", line),
+            other => panic!("{}: {:?}", line, other),
+        }
+    }
+}
+
+#[test]
+fn missing_fields() {
+    let (_, save) = saved_on("maze.txt");
+    let text = save.to_text();
+    for field in ["player", "level", "view", "score"] {
+        let without = replace_line(&text, &format!("{} =", field), "# quitado");
+        assert!(matches!(SaveGame::parse(&without), Err(SaveError::MissingField(f)) if f == field), "{}", field);
+    }
+}
+
+#[test]
+fn non_finite_floats_are_corrupt() {
+    let (_, save) = saved_on("maze.txt");
+    let text = save.to_text();
+    let cases = [
+        ("player", "player = NaN 40 0 1"),
+        ("player", "player = 40 40 inf 1"),
+        ("cat", "cat = 40 -inf 0"),
+        ("elapsed", "elapsed = NaN"),
+        ("invulnerable", "invulnerable = inf"),
+        ("trail", "trail = 1 2 NaN 4"),
+    ];
+    for (key, line) in cases {
+        let text = if text.lines().any(|l| l.starts_with(key)) {
+            replace_line(&text, key, line)
+        } else {
+            format!("{}\n{}\n", text, line)
+        };
+        assert!(matches!(SaveGame::parse(&text), Err(SaveError::Corrupt { .. })), "{}", line);
+    }
+}
+
+#[test]
+fn restore_rejects_another_level() {
+    let (_, save) = saved_on("maze2.txt");
+    let mut game = Game::new(vec!["maze.txt".to_string()], 5, Settings::default());
+    assert!(matches!(game.restore(&save), Err(SaveError::LevelMismatch(_))));
+
+    // Hecha en `maze2.txt` pero diciendo que es `maze.txt`
+    let mut renamed = save.clone();
+    renamed.level = SavedLevel::File("maze.txt".to_string());
+    assert!(matches!(game.restore(&renamed), Err(SaveError::LevelMismatch(_))));
+}
+
+#[test]
+fn restore_rejects_positions_and_view_out_of_range() {
+    let (mut game, save) = saved_on("maze.txt");
+    game.restore(&save).unwrap();
+
+    let mut cat_outside = save.clone();
+    cat_outside.cats[0].0.x = -10.0;
+    let mut player_outside = save.clone();
+    player_outside.player_pos.y = 1.0e6;
+    let mut narrow = save.clone();
+    narrow.player_fov = 0.0;
+    let mut wide = save.clone();
+    wide.player_fov = 10.0;
+    for bad in [cat_outside, player_outside, narrow, wide] {
+        assert!(matches!(game.restore(&bad), Err(SaveError::LevelMismatch(_))), "{:?}", bad);
+    }
+}
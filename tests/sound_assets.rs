@@ -0,0 +1,33 @@
+// Todos los sonidos que usa el juego están en `assets/sounds`
+use raycaster::audio::*;
+
+#[test]
+fn every_sound_is_shipped() {
+    let sounds = [
+        MENU_MUSIC,
+        WIN_MUSIC,
+        LOSE_MUSIC,
+        TENSION_LAYER,
+        STINGER_WIN,
+        STINGER_LOSE,
+        STEP_DIRT,
+        STEP_STONE,
+        STEP_GRASS,
+        SFX_CARROT,
+        SFX_HURT,
+        SFX_LIFE_LOST,
+        SFX_HEAL,
+        SFX_KEY,
+        SFX_DOOR_OPEN,
+        SFX_DOOR_LOCKED,
+        SFX_LEVEL_COMPLETE,
+        SFX_MEOW,
+        CARROT_HUM_LOOP,
+    ];
+    for sound in sounds.iter().chain(LEVEL_MUSIC.iter()) {
+        let bytes = std::fs::read(sound).unwrap_or_else(|err| panic!("{}: {}", sound, err));
+        if sound.ends_with(".wav") {
+            assert!(bytes.starts_with(b"RIFF") && bytes[8..12] == *b"WAVE", "{} no es un WAV", sound);
+        }
+    }
+}
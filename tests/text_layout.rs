@@ -0,0 +1,58 @@
+// Partido de líneas de una pasada y caché de glifos acotada
+use raycaster::framebuffer::Framebuffer;
+use raycaster::text::{TextStyle, MAX_CACHED_GLYPHS, TEXT};
+
+// El partido de antes, midiendo cada prefijo: el nuevo tiene que dar lo mismo
+fn reference_lines(text: &str, size: f32, max_width: usize) -> Vec<&str> {
+    let width = |line: &str| TEXT.measure(line, &TextStyle::new(size)).0;
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let (mut start, mut last_break) = (0, None);
+        for (idx, c) in paragraph.char_indices() {
+            if c == ' ' {
+                last_break = Some(idx);
+            }
+            if width(&paragraph[start..idx + c.len_utf8()]) > max_width {
+                if let Some(brk) = last_break.filter(|&b| b > start) {
+                    lines.push(&paragraph[start..brk]);
+                    start = brk + 1;
+                    last_break = None;
+                }
+            }
+        }
+        lines.push(&paragraph[start..]);
+    }
+    lines
+}
+
+#[test]
+fn wrapping_matches_prefix_measurement() {
+    let texts = [
+        "El conejo corre por el laberinto buscando zanahorias mientras el gato patrulla",
+        "Palabrasuperlargaquenocabeenningunalinea y luego texto normal",
+        "Dos párrafos\ncon un salto de línea y acentos: áéíóú ñ",
+        "",
+    ];
+    for text in texts {
+        for (size, max_width) in [(20.0, 120), (30.0, 200), (13.25, 75)] {
+            let style = TextStyle::new(size).wrap(max_width);
+            let expected = reference_lines(text, size, max_width);
+            let expected_width = expected.iter().map(|l| TEXT.measure(l, &TextStyle::new(size)).0).max().unwrap_or(0);
+            let line_height = TEXT.line_height(size);
+            let expected_height = (line_height * expected.len() as f32).ceil() as usize;
+            assert_eq!(TEXT.measure(text, &style), (expected_width, expected_height), "{:?} a {}", text, size);
+        }
+    }
+}
+
+#[test]
+fn glyph_cache_is_bounded() {
+    let mut buffer = vec![0u8; 64 * 64 * 4];
+    let mut framebuffer = Framebuffer::new(64, 64, &mut buffer);
+    // Como al redimensionar la ventana poco a poco: cada tamaño es nuevo
+    for step in 0..400 {
+        let style = TextStyle::new(10.0 + step as f32 * 0.25);
+        framebuffer.draw_text_styled("Puntos: 0123456789", 0, 0, &style);
+        assert!(TEXT.cached_glyphs() <= MAX_CACHED_GLYPHS);
+    }
+}